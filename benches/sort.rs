@@ -0,0 +1,75 @@
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use rand::distributions::{Distribution, Uniform};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+const N: usize = 10_000;
+
+fn random_vec(n_distinct: i64) -> Vec<i64> {
+    let mut rng = StdRng::seed_from_u64(0);
+    let ud = Uniform::new(0, n_distinct);
+    (0..N).map(|_| ud.sample(&mut rng)).collect()
+}
+
+fn bench_sort(c: &mut Criterion) {
+    let mut group = c.benchmark_group("sort");
+    for &n_distinct in [10, 100, 1_000, 10_000].iter() {
+        let v = random_vec(n_distinct);
+        group.bench_with_input(BenchmarkId::new("avlsort", n_distinct), &v, |b, v| {
+            b.iter(|| avlsort::sort(&mut black_box(v.clone())))
+        });
+        group.bench_with_input(BenchmarkId::new("slice::sort", n_distinct), &v, |b, v| {
+            b.iter(|| black_box(v.clone()).sort())
+        });
+        group.bench_with_input(BenchmarkId::new("slice::sort_unstable", n_distinct), &v, |b, v| {
+            b.iter(|| black_box(v.clone()).sort_unstable())
+        });
+    }
+    group.finish();
+}
+
+fn bench_argsort(c: &mut Criterion) {
+    let mut group = c.benchmark_group("argsort");
+    for &n_distinct in [10, 10_000].iter() {
+        let v = random_vec(n_distinct);
+        group.bench_with_input(BenchmarkId::new("avlsort", n_distinct), &v, |b, v| {
+            b.iter(|| avlsort::argsort(black_box(v)))
+        });
+        group.bench_with_input(BenchmarkId::new("slice::sort_by_key", n_distinct), &v, |b, v| {
+            b.iter(|| {
+                let mut indices: Vec<usize> = (0..v.len()).collect();
+                indices.sort_by_key(|&i| black_box(v)[i]);
+                indices
+            })
+        });
+    }
+    group.finish();
+}
+
+fn bench_dedup_counts(c: &mut Criterion) {
+    let mut group = c.benchmark_group("sort_dedup_counts");
+    for &n_distinct in [10, 10_000].iter() {
+        let v = random_vec(n_distinct);
+        group.bench_with_input(BenchmarkId::new("avlsort", n_distinct), &v, |b, v| {
+            b.iter(|| avlsort::sort_dedup_counts(black_box(v)))
+        });
+        group.bench_with_input(BenchmarkId::new("slice::sort_unstable+dedup", n_distinct), &v, |b, v| {
+            b.iter(|| {
+                let mut v = black_box(v.clone());
+                v.sort_unstable();
+                let mut counts: Vec<(i64, usize)> = Vec::new();
+                for value in v {
+                    match counts.last_mut() {
+                        Some((last, count)) if *last == value => *count += 1,
+                        _ => counts.push((value, 1)),
+                    }
+                }
+                counts
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_sort, bench_argsort, bench_dedup_counts);
+criterion_main!(benches);
@@ -9,7 +9,7 @@ fn main() {
         println!("value={}| rank={}, dup={}", i, rank, dup);
     }
     println!("Elements in the tree = {}", g.len());
-    println!("");
+    println!();
 
     println!("Height of the tree = {}", g.height());
     println!("How many 4?: {}", g.count(4));
@@ -17,18 +17,15 @@ fn main() {
     println!("4 is in?: {}", g.isin(4));
     println!("remove 4: {:?}", g.remove(4));
     println!("4 is in?: {}", g.isin(4));
-    println!("");
+    println!();
 
     println!("Max value = {}", g.max().unwrap());
     let (value, dup) = g.pop_max_all().unwrap();
     println!("Pop all the max value: value={}, dup={}", value, dup);
-    println!("");
+    println!();
 
     println!("=== Show elements in ascending order. ===");
-    loop {
-        match g.pop_min() {
-            Some(value) => println!("Min value = {}", value),
-            None => break,
-        }
+    while let Some(value) = g.pop_min() {
+        println!("Min value = {}", value);
     }
 }
\ No newline at end of file
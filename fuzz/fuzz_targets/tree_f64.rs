@@ -0,0 +1,17 @@
+#![no_main]
+
+use avlsort_fuzz::{decode, run, Input};
+use libfuzzer_sys::fuzz_target;
+
+/// Values which are hard for comparisons.
+const SPECIAL: [f64; 8] = [f64::NAN, -0.0, 0.0, f64::INFINITY, f64::NEG_INFINITY, f64::MIN_POSITIVE, f64::MAX, f64::MIN];
+
+fuzz_target!(|data: &[u8]| {
+    let mut input = Input::new(data);
+    let (limit, ops) = decode(&mut input, |input| match input.byte()? {
+        b @ 0..=7 => Some(SPECIAL[b as usize]),
+        8 => Some(f64::from_le_bytes(input.bytes()?)),
+        b => Some((b as i8) as f64 / 4.0),
+    });
+    run(limit, &ops);
+});
@@ -0,0 +1,15 @@
+#![no_main]
+
+use avlsort_fuzz::{decode, run, run_model, Input};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let mut input = Input::new(data);
+    // Mostly small values, so that duplicates and collisions are frequent.
+    let (limit, ops) = decode(&mut input, |input| match input.byte()? {
+        255 => Some(i32::from_le_bytes(input.bytes()?)),
+        b => Some(b as i8 as i32 / 4),
+    });
+    run(limit, &ops);
+    run_model(&ops);
+});
@@ -0,0 +1,25 @@
+#![no_main]
+
+use avlsort::traits::OrdEqElem;
+use avlsort_fuzz::{decode, run, Input};
+use libfuzzer_sys::fuzz_target;
+
+/// Values which are hard for comparisons, except NaN which `OrdEqElem` rejects by panicking.
+const SPECIAL: [f64; 7] = [-0.0, 0.0, f64::INFINITY, f64::NEG_INFINITY, f64::MIN_POSITIVE, f64::MAX, f64::MIN];
+
+// `OrdEqElem` breaks ties randomly, so a crash may not reproduce on every run.
+fuzz_target!(|data: &[u8]| {
+    let mut input = Input::new(data);
+    let (limit, ops) = decode(&mut input, |input| {
+        let value = match input.byte()? {
+            b @ 0..=6 => SPECIAL[b as usize],
+            7 => {
+                let value = f64::from_le_bytes(input.bytes()?);
+                if value.is_nan() { 0.0 } else { value }
+            }
+            b => (b as i8) as f64 / 4.0,
+        };
+        Some(OrdEqElem::new(value))
+    });
+    run(limit, &ops);
+});
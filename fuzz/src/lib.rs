@@ -0,0 +1,310 @@
+//! Operations on AVL tree decoded from fuzz input.
+//!
+//! The trees are built with the `paranoid` feature, so every mutation is validated as well.
+
+use std::collections::BTreeMap;
+use std::fmt::Debug;
+
+use avlsort::traits::TreeElem;
+use avlsort::tree::{AvlTree, Evict};
+
+/// Bytes of fuzz input read from the front.
+pub struct Input<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> Input<'a> {
+    pub fn new(data: &'a [u8]) -> Self {
+        Self { data }
+    }
+
+    /// Read a byte, or `None` at the end.
+    pub fn byte(&mut self) -> Option<u8> {
+        let (&b, rest) = self.data.split_first()?;
+        self.data = rest;
+        Some(b)
+    }
+
+    /// Read `N` bytes, or `None` at the end.
+    pub fn bytes<const N: usize>(&mut self) -> Option<[u8; N]> {
+        if self.data.len() < N {
+            return None;
+        }
+        let (head, rest) = self.data.split_at(N);
+        self.data = rest;
+        let mut buf = [0; N];
+        buf.copy_from_slice(head);
+        Some(buf)
+    }
+}
+
+/// Operations on the public API of the tree.
+#[derive(Clone, Copy, Debug)]
+pub enum Op<T> {
+    Push(T),
+    Remove(T),
+    RemoveN(T, usize),
+    Replace(T, T),
+    PopMin,
+    PopMax,
+    PopMinAll,
+    PopMaxAll,
+    Query(T),
+    Nth(usize),
+    Increment(T, usize),
+    Decrement(T, usize),
+    Retain(T),
+    CursorRemove(T),
+}
+
+/// Decode the capacity limit and the operations from `input`, reading values by `value`.
+pub fn decode<T, F>(input: &mut Input<'_>, mut value: F) -> (Option<(usize, Evict)>, Vec<Op<T>>)
+where
+    F: FnMut(&mut Input<'_>) -> Option<T>,
+{
+    let limit = match input.byte() {
+        Some(b) if b & 1 == 1 => {
+            let evict = if b & 2 == 0 { Evict::Min } else { Evict::Max };
+            Some(((b >> 2) as usize + 1, evict))
+        }
+        _ => None,
+    };
+    let mut ops = Vec::new();
+    // The last operation may be cut off by the end of the input.
+    while let Some(op) = decode_op(input, &mut value) {
+        ops.push(op);
+    }
+    (limit, ops)
+}
+
+/// Decode an operation from `input`, or `None` at the end.
+fn decode_op<T, F>(input: &mut Input<'_>, value: &mut F) -> Option<Op<T>>
+where
+    F: FnMut(&mut Input<'_>) -> Option<T>,
+{
+    let op = match input.byte()? % 14 {
+        0 => Op::Push(value(input)?),
+        1 => Op::Remove(value(input)?),
+        2 => Op::RemoveN(value(input)?, input.byte()? as usize % 4),
+        3 => Op::Replace(value(input)?, value(input)?),
+        4 => Op::PopMin,
+        5 => Op::PopMax,
+        6 => Op::PopMinAll,
+        7 => Op::PopMaxAll,
+        8 => Op::Query(value(input)?),
+        9 => Op::Nth(input.byte()? as usize),
+        10 => Op::Increment(value(input)?, input.byte()? as usize % 4),
+        11 => Op::Decrement(value(input)?, input.byte()? as usize % 4),
+        12 => Op::Retain(value(input)?),
+        _ => Op::CursorRemove(value(input)?),
+    };
+    Some(op)
+}
+
+/// Apply `ops` to a tree with `limit` and check the invariants after every step.
+///
+/// Only the properties which hold for NaN and `OrdEqElem` are checked.
+pub fn run<T: TreeElem + Debug>(limit: Option<(usize, Evict)>, ops: &[Op<T>]) {
+    let mut tree = match limit {
+        Some((n, evict)) => AvlTree::with_capacity_limit(n, evict),
+        None => AvlTree::new(),
+    };
+    let mut len = 0;
+    for &op in ops {
+        match op {
+            Op::Push(v) => {
+                let (_, evicted) = tree.push_evict(v);
+                if evicted.is_none() {
+                    len += 1;
+                }
+            }
+            Op::Remove(v) => {
+                if tree.remove(v).is_ok() {
+                    len -= 1;
+                }
+            }
+            Op::RemoveN(v, n) => {
+                let removed = tree.remove_n(v, n);
+                assert!(removed <= n);
+                len -= removed;
+            }
+            Op::Replace(old, new) => {
+                // Replacing never changes the length, but `new` may be evicted.
+                let _ = tree.replace(old, new);
+                len = tree.len();
+            }
+            Op::PopMin => {
+                if tree.pop_min().is_some() {
+                    len -= 1;
+                }
+            }
+            Op::PopMax => {
+                if tree.pop_max().is_some() {
+                    len -= 1;
+                }
+            }
+            Op::PopMinAll => {
+                if let Some((_, dup)) = tree.pop_min_all() {
+                    len -= dup + 1;
+                }
+            }
+            Op::PopMaxAll => {
+                if let Some((_, dup)) = tree.pop_max_all() {
+                    len -= dup + 1;
+                }
+            }
+            Op::Query(v) => {
+                let (rank, rank_upper) = (tree.rank(v), tree.rank_upper(v));
+                assert!(rank <= len && rank_upper <= len, "{:?}: rank={}, rank_upper={}", op, rank, rank_upper);
+                assert_eq!(tree.isin(v), tree.count(v) > 0, "{:?}", op);
+            }
+            Op::Nth(k) => assert_eq!(tree.nth(k).is_some(), k < len, "{:?}", op),
+            Op::Increment(v, n) => {
+                let _ = tree.entry(v).increment(n);
+                len = tree.len();
+            }
+            Op::Decrement(v, n) => {
+                let _ = tree.entry(v).decrement(n);
+                len = tree.len();
+            }
+            Op::Retain(v) => {
+                tree.retain(|&x, _| x != v);
+                len = tree.len();
+            }
+            Op::CursorRemove(v) => {
+                if tree.lower_bound_cursor_mut(v).remove_current().is_some() {
+                    len -= 1;
+                }
+            }
+        }
+
+        if let Err(report) = tree.validate() {
+            panic!("{:?} broke the tree: {}", op, report);
+        }
+        assert_eq!(tree.len(), len, "{:?}", op);
+        assert_eq!(tree.is_empty(), len == 0, "{:?}", op);
+        if let Some((n, _)) = limit {
+            assert!(len <= n, "{:?}: {} elements over the limit {}", op, len, n);
+        }
+        let counts = tree.to_vec_counts();
+        assert_eq!(counts.iter().map(|&(_, count)| count).sum::<usize>(), len, "{:?}", op);
+        assert_eq!(tree.distinct_len(), counts.len(), "{:?}", op);
+        assert_eq!(tree.min().is_some(), len > 0, "{:?}", op);
+        assert_eq!(tree.max().is_some(), len > 0, "{:?}", op);
+        assert_eq!(tree.mode().is_some(), len > 0, "{:?}", op);
+    }
+}
+
+/// Apply `ops` to a tree without a capacity limit and to `BTreeMap`, and compare the results.
+pub fn run_model(ops: &[Op<i32>]) {
+    let mut tree = AvlTree::new();
+    let mut model: BTreeMap<i32, usize> = BTreeMap::new();
+    let rank = |model: &BTreeMap<i32, usize>, v: i32| model.range(..v).map(|(_, &c)| c).sum::<usize>();
+    let count = |model: &BTreeMap<i32, usize>, v: i32| model.get(&v).copied().unwrap_or(0);
+    let remove_n = |model: &mut BTreeMap<i32, usize>, v: i32, n: usize| {
+        let c = model.get(&v).copied().unwrap_or(0);
+        let removed = c.min(n);
+        if removed == c {
+            model.remove(&v);
+        } else {
+            *model.get_mut(&v).unwrap() -= removed;
+        }
+        removed
+    };
+    for &op in ops {
+        match op {
+            Op::Push(v) => {
+                let expected = (rank(&model, v), count(&model, v));
+                *model.entry(v).or_insert(0) += 1;
+                assert_eq!(tree.push(v), expected, "{:?}", op);
+            }
+            Op::CursorRemove(v) => {
+                let expected = model.range(v..).next().map(|(&x, _)| x);
+                if let Some(x) = expected {
+                    remove_n(&mut model, x, 1);
+                }
+                assert_eq!(tree.lower_bound_cursor_mut(v).remove_current(), expected, "{:?}", op);
+            }
+            Op::Remove(v) => {
+                let expected = remove_n(&mut model, v, 1) == 1;
+                assert_eq!(tree.remove(v).is_ok(), expected, "{:?}", op);
+            }
+            Op::RemoveN(v, n) => {
+                let expected = remove_n(&mut model, v, n);
+                assert_eq!(tree.remove_n(v, n), expected, "{:?}", op);
+            }
+            Op::Increment(v, n) => {
+                let entry = tree.entry(v);
+                assert_eq!((entry.rank(), entry.count()), (rank(&model, v), count(&model, v)), "{:?}", op);
+                if n > 0 {
+                    *model.entry(v).or_insert(0) += n;
+                }
+                let entry = entry.increment(n);
+                assert_eq!((entry.rank(), entry.count()), (rank(&model, v), count(&model, v)), "{:?}", op);
+            }
+            Op::Decrement(v, n) => {
+                let entry = tree.entry(v);
+                assert_eq!((entry.rank(), entry.count()), (rank(&model, v), count(&model, v)), "{:?}", op);
+                remove_n(&mut model, v, n);
+                let entry = entry.decrement(n);
+                assert_eq!((entry.rank(), entry.count()), (rank(&model, v), count(&model, v)), "{:?}", op);
+            }
+            Op::Replace(old, new) => {
+                let expected = if count(&model, old) > 0 {
+                    let old_rank = rank(&model, old);
+                    remove_n(&mut model, old, 1);
+                    let new_rank_dup = (rank(&model, new), count(&model, new));
+                    *model.entry(new).or_insert(0) += 1;
+                    Some((old_rank, new_rank_dup.0, new_rank_dup.1))
+                } else {
+                    None
+                };
+                assert_eq!(tree.replace(old, new).ok(), expected, "{:?}", op);
+            }
+            Op::PopMin => {
+                let expected = model.keys().next().copied();
+                if let Some(v) = expected {
+                    remove_n(&mut model, v, 1);
+                }
+                assert_eq!(tree.pop_min(), expected, "{:?}", op);
+            }
+            Op::PopMax => {
+                let expected = model.keys().next_back().copied();
+                if let Some(v) = expected {
+                    remove_n(&mut model, v, 1);
+                }
+                assert_eq!(tree.pop_max(), expected, "{:?}", op);
+            }
+            Op::PopMinAll => {
+                let expected = model.iter().next().map(|(&v, &c)| (v, c - 1));
+                if let Some((v, _)) = expected {
+                    model.remove(&v);
+                }
+                assert_eq!(tree.pop_min_all(), expected, "{:?}", op);
+            }
+            Op::PopMaxAll => {
+                let expected = model.iter().next_back().map(|(&v, &c)| (v, c - 1));
+                if let Some((v, _)) = expected {
+                    model.remove(&v);
+                }
+                assert_eq!(tree.pop_max_all(), expected, "{:?}", op);
+            }
+            Op::Query(v) => {
+                assert_eq!(tree.count(v), count(&model, v), "{:?}", op);
+                assert_eq!(tree.rank(v), rank(&model, v), "{:?}", op);
+                assert_eq!(tree.rank_upper(v), rank(&model, v) + count(&model, v), "{:?}", op);
+            }
+            Op::Nth(k) => {
+                let mut rest = k;
+                let expected = model.iter().find(|&(_, &c)| rest < c || { rest -= c; false }).map(|(&v, _)| v);
+                assert_eq!(tree.nth(k), expected, "{:?}", op);
+            }
+            Op::Retain(v) => {
+                model.remove(&v);
+                tree.retain(|&x, _| x != v);
+            }
+        }
+        let expected: Vec<(i32, usize)> = model.iter().map(|(&v, &c)| (v, c)).collect();
+        assert_eq!(tree.to_vec_counts(), expected, "{:?}", op);
+    }
+}
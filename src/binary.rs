@@ -0,0 +1,167 @@
+//! Binary format of AVL tree.
+//!
+//! All numbers are little endian.
+//!
+//! | Field | Size |
+//! | --- | --- |
+//! | Magic bytes `AVLT` | 4 |
+//! | Version | 1 |
+//! | Type tag of the element | 1 |
+//! | Number of distinct values `d` | 8 |
+//! | `d` pairs of a value and the number of it in ascending order | `d * (size_of_value + 8)` |
+//!
+//! `isize` and `usize` are stored in 8 bytes.
+//! The capacity limit of the tree is not stored.
+
+use std::cmp::Ordering;
+use std::convert::TryInto;
+use std::io::{self, Read, Write};
+
+use crate::error::DecodeError;
+use crate::traits::{cmp_elem, is_comparable, OrdEqElem, TreeElem};
+use crate::tree::AvlTree;
+
+/// Magic bytes at the beginning of the format.
+pub const MAGIC: [u8; 4] = *b"AVLT";
+/// Version of the format.
+pub const VERSION: u8 = 1;
+
+/// Elements which can be written in the binary format.
+pub trait BinaryElem: TreeElem {
+    /// Tag of the element type in the header.
+    const TYPE_TAG: u8;
+
+    /// Write the element to `w`.
+    fn write_elem<W: Write>(&self, w: &mut W) -> io::Result<()>;
+
+    /// Read an element from `r`.
+    fn read_elem<R: Read>(r: &mut R) -> Result<Self, DecodeError>;
+}
+
+macro_rules! impl_binary_elem {
+    ($($t:ty => $tag:expr),*) => {
+        $(
+            impl BinaryElem for $t {
+                const TYPE_TAG: u8 = $tag;
+
+                fn write_elem<W: Write>(&self, w: &mut W) -> io::Result<()> {
+                    w.write_all(&self.to_le_bytes())
+                }
+
+                fn read_elem<R: Read>(r: &mut R) -> Result<Self, DecodeError> {
+                    let mut buf = [0; std::mem::size_of::<$t>()];
+                    r.read_exact(&mut buf)?;
+                    Ok(<$t>::from_le_bytes(buf))
+                }
+            }
+        )*
+    };
+}
+
+impl_binary_elem!(
+    f32 => 1, f64 => 2,
+    i8 => 3, i16 => 4, i32 => 5, i64 => 6, i128 => 7,
+    u8 => 9, u16 => 10, u32 => 11, u64 => 12, u128 => 13
+);
+
+impl BinaryElem for isize {
+    const TYPE_TAG: u8 = 8;
+
+    fn write_elem<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        (*self as i64).write_elem(w)
+    }
+
+    fn read_elem<R: Read>(r: &mut R) -> Result<Self, DecodeError> {
+        let value = i64::read_elem(r)?;
+        value.try_into().map_err(|_| DecodeError::Overflow)
+    }
+}
+
+impl BinaryElem for usize {
+    const TYPE_TAG: u8 = 14;
+
+    fn write_elem<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        (*self as u64).write_elem(w)
+    }
+
+    fn read_elem<R: Read>(r: &mut R) -> Result<Self, DecodeError> {
+        let value = u64::read_elem(r)?;
+        value.try_into().map_err(|_| DecodeError::Overflow)
+    }
+}
+
+impl<T: BinaryElem> BinaryElem for OrdEqElem<T> {
+    const TYPE_TAG: u8 = 0x80 | T::TYPE_TAG;
+
+    fn write_elem<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        self.value.write_elem(w)
+    }
+
+    fn read_elem<R: Read>(r: &mut R) -> Result<Self, DecodeError> {
+        Ok(Self::new(T::read_elem(r)?))
+    }
+}
+
+impl<T: BinaryElem> AvlTree<T> {
+    /// Write the tree to `w` in the binary format.
+    ///
+    /// A tree holding NaN can be written, but cannot be read back.
+    pub fn write_to<W: Write>(&self, mut w: W) -> io::Result<()> {
+        let counts = self.merged_counts();
+        w.write_all(&MAGIC)?;
+        w.write_all(&[VERSION, T::TYPE_TAG])?;
+        (counts.len() as u64).write_elem(&mut w)?;
+        for (value, count) in counts {
+            value.write_elem(&mut w)?;
+            (count as u64).write_elem(&mut w)?;
+        }
+        w.flush()
+    }
+
+    /// Read a tree in the binary format from `r`, and build it in `O(n)`.
+    pub fn read_from<R: Read>(mut r: R) -> Result<Self, DecodeError> {
+        let mut magic = [0; 4];
+        r.read_exact(&mut magic)?;
+        if magic != MAGIC {
+            return Err(DecodeError::BadMagic);
+        }
+        let mut header = [0; 2];
+        r.read_exact(&mut header)?;
+        if header[0] != VERSION {
+            return Err(DecodeError::UnsupportedVersion(header[0]));
+        }
+        if header[1] != T::TYPE_TAG {
+            return Err(DecodeError::TypeMismatch { expected: T::TYPE_TAG, found: header[1] });
+        }
+        let n_distinct = u64::read_elem(&mut r)?;
+
+        // The number of distinct values is not trusted to allocate the memory.
+        let mut counts: Vec<(T, usize)> = Vec::with_capacity(n_distinct.min(1 << 16) as usize);
+        let mut len: usize = 0;
+        for i in 0..n_distinct {
+            let value = T::read_elem(&mut r)?;
+            let count = usize::read_elem(&mut r)?;
+            check_next(counts.last().map(|&(prev, _)| prev), value, count, i)?;
+            len = len.checked_add(count).ok_or(DecodeError::Overflow)?;
+            counts.push((value, count));
+        }
+        Ok(Self::from_sorted_counts(&counts))
+    }
+}
+
+/// Check that `value` with the number `count` at the index `i` can follow `prev`.
+pub(crate) fn check_next<T: TreeElem>(prev: Option<T>, value: T, count: usize, i: u64) -> Result<(), DecodeError> {
+    if count == 0 {
+        return Err(DecodeError::ZeroCount(i));
+    }
+    // `<` of `OrdEqElem` panics on NaN, so NaN is rejected first.
+    let sorted = is_comparable(&value) && match prev {
+        Some(prev) => cmp_elem(prev, value) == Ordering::Less,
+        None => true,
+    };
+    if sorted {
+        Ok(())
+    } else {
+        Err(DecodeError::Unsorted(i))
+    }
+}
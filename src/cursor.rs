@@ -0,0 +1,405 @@
+//! Cursors over the distinct values of AVL tree.
+//!
+//! A cursor points at a distinct value of the tree, or at the "ghost" position
+//! between the maximum and the minimum value.
+
+use std::sync::{Arc, Mutex};
+
+use crate::node::AvlNode;
+use crate::traits::TreeElem;
+use crate::tree::AvlTree;
+
+/// Position of a cursor.
+struct Position<T> {
+    /// Nodes from the child of the root to the current node.
+    /// If it is empty, the current node is the root.
+    path: Vec<Arc<Mutex<AvlNode<T>>>>,
+    /// Whether each node in `path` is the left child of its parent.
+    is_left: Vec<bool>,
+    /// The number of elements smaller than the current value.
+    rank: usize,
+    ghost: bool,
+}
+
+impl<T: TreeElem> Position<T> {
+    fn ghost() -> Self {
+        Self { path: Vec::new(), is_left: Vec::new(), rank: 0, ghost: true }
+    }
+
+    /// Apply `f` to the current node.
+    fn with_node<R>(&self, root: &AvlNode<T>, f: impl FnOnce(&AvlNode<T>) -> R) -> R {
+        match self.path.last() {
+            Some(node) => f(&node.lock().unwrap()),
+            None => f(root),
+        }
+    }
+
+    fn current(&self, root: Option<&AvlNode<T>>) -> Option<(T, usize, usize)> {
+        match root {
+            Some(r) if !self.ghost => {
+                Some(self.with_node(r, |n| (n.value, self.rank, n.n_ledu.1)))
+            }
+            _ => None,
+        }
+    }
+
+    /// Go down from the current node to the child on `left` side, then to the end on the other side.
+    fn descend(&mut self, root: &AvlNode<T>, left: bool) {
+        let mut next = self.with_node(root, |n| if left { n.left.clone() } else { n.right.clone() });
+        let mut is_left = left;
+        while let Some(node) = next {
+            next = {
+                let n = node.lock().unwrap();
+                if left { n.right.clone() } else { n.left.clone() }
+            };
+            self.path.push(node);
+            self.is_left.push(is_left);
+            is_left = !left;
+        }
+    }
+
+    fn first(root: Option<&AvlNode<T>>) -> Self {
+        let mut pos = Self::ghost();
+        if let Some(r) = root {
+            pos.ghost = false;
+            pos.descend_to_end(r, true);
+        }
+        pos
+    }
+
+    fn last(root: Option<&AvlNode<T>>) -> Self {
+        let mut pos = Self::ghost();
+        if let Some(r) = root {
+            pos.ghost = false;
+            pos.descend_to_end(r, false);
+            pos.rank = r.len_child_and_self() - pos.with_node(r, |n| n.n_ledu.1 + 1);
+        }
+        pos
+    }
+
+    /// Go down from the root to the minimum (`left`) or the maximum value.
+    fn descend_to_end(&mut self, root: &AvlNode<T>, left: bool) {
+        let mut next = if left { root.left.clone() } else { root.right.clone() };
+        while let Some(node) = next {
+            next = {
+                let n = node.lock().unwrap();
+                if left { n.left.clone() } else { n.right.clone() }
+            };
+            self.path.push(node);
+            self.is_left.push(left);
+        }
+    }
+
+    /// Point at the smallest value larger than or equal to `value` (`strict == false`),
+    /// or larger than `value` (`strict == true`).
+    fn lower_bound(root: Option<&AvlNode<T>>, value: T, strict: bool) -> Self {
+        let mut pos = Self::ghost();
+        let r = match root {
+            Some(r) => r,
+            None => return pos,
+        };
+        // `(depth, rank)` of the best node so far.
+        let mut found = None;
+        let mut n_less = 0;
+        let (goes_left, n_ledu, mut next) = {
+            let goes_left = if strict { value < r.value } else { value <= r.value };
+            (goes_left, r.n_ledu, if goes_left { r.left.clone() } else { r.right.clone() })
+        };
+        if goes_left {
+            found = Some((0, n_less + n_ledu.0));
+        } else {
+            n_less += n_ledu.0 + n_ledu.1 + 1;
+        }
+        pos.is_left.push(goes_left);
+        while let Some(node) = next {
+            let (goes_left, n_ledu, child) = {
+                let n = node.lock().unwrap();
+                let goes_left = if strict { value < n.value } else { value <= n.value };
+                (goes_left, n.n_ledu, if goes_left { n.left.clone() } else { n.right.clone() })
+            };
+            pos.path.push(node);
+            if goes_left {
+                found = Some((pos.path.len(), n_less + n_ledu.0));
+            } else {
+                n_less += n_ledu.0 + n_ledu.1 + 1;
+            }
+            pos.is_left.push(goes_left);
+            next = child;
+        }
+        match found {
+            Some((depth, rank)) => {
+                pos.path.truncate(depth);
+                pos.is_left.truncate(depth);
+                pos.rank = rank;
+                pos.ghost = false;
+                pos
+            }
+            None => Self::ghost(),
+        }
+    }
+
+    /// Point at the value of the `k`-th smallest element (0-indexed).
+    fn nth(root: Option<&AvlNode<T>>, k: usize) -> Self {
+        let mut pos = Self::ghost();
+        let r = match root {
+            Some(r) => r,
+            None => return pos,
+        };
+        let mut k = k;
+        let mut n_less = 0;
+        let mut node: Option<Arc<Mutex<AvlNode<T>>>> = None;
+        loop {
+            let (n_ledu, left, right) = match &node {
+                Some(node) => {
+                    let n = node.lock().unwrap();
+                    (n.n_ledu, n.left.clone(), n.right.clone())
+                }
+                None => (r.n_ledu, r.left.clone(), r.right.clone()),
+            };
+            if let Some(node) = node.take() {
+                pos.path.push(node);
+            }
+            let next = if k < n_ledu.0 {
+                pos.is_left.push(true);
+                left
+            } else if k <= n_ledu.0 + n_ledu.1 {
+                pos.rank = n_less + n_ledu.0;
+                pos.ghost = false;
+                pos.is_left.truncate(pos.path.len());
+                return pos;
+            } else {
+                k -= n_ledu.0 + n_ledu.1 + 1;
+                n_less += n_ledu.0 + n_ledu.1 + 1;
+                pos.is_left.push(false);
+                right
+            };
+            match next {
+                Some(next) => node = Some(next),
+                None => return Self::ghost(),
+            }
+        }
+    }
+
+    fn move_next(&mut self, root: Option<&AvlNode<T>>) {
+        let r = match root {
+            Some(r) => r,
+            None => return,
+        };
+        if self.ghost {
+            *self = Self::first(root);
+            return;
+        }
+        let (count, has_right) = self.with_node(r, |n| (n.n_ledu.1 + 1, n.right.is_some()));
+        self.rank += count;
+        if has_right {
+            self.descend(r, false);
+            return;
+        }
+        // Go up until coming from a left child.
+        loop {
+            match self.is_left.pop() {
+                Some(is_left) => {
+                    self.path.pop();
+                    if is_left {
+                        return;
+                    }
+                }
+                None => {
+                    *self = Self::ghost();
+                    return;
+                }
+            }
+        }
+    }
+
+    fn move_prev(&mut self, root: Option<&AvlNode<T>>) {
+        let r = match root {
+            Some(r) => r,
+            None => return,
+        };
+        if self.ghost {
+            *self = Self::last(root);
+            return;
+        }
+        let has_left = self.with_node(r, |n| n.left.is_some());
+        if has_left {
+            self.descend(r, true);
+        } else {
+            // Go up until coming from a right child.
+            loop {
+                match self.is_left.pop() {
+                    Some(is_left) => {
+                        self.path.pop();
+                        if !is_left {
+                            break;
+                        }
+                    }
+                    None => {
+                        *self = Self::ghost();
+                        return;
+                    }
+                }
+            }
+        }
+        self.rank -= self.with_node(r, |n| n.n_ledu.1 + 1);
+    }
+}
+
+/// Cursor over the distinct values of AVL tree.
+pub struct Cursor<'a, T> {
+    tree: &'a AvlTree<T>,
+    pos: Position<T>,
+}
+
+impl<'a, T: TreeElem> Cursor<'a, T> {
+    /// Return `(value, rank, number_of_duplicates)` of the current value,
+    /// or `None` at the ghost position.
+    pub fn current(&self) -> Option<(T, usize, usize)> {
+        self.pos.current(self.tree.root.as_ref())
+    }
+
+    /// Move to the next larger value.
+    ///
+    /// From the maximum value, move to the ghost position; from the ghost position, move to the minimum value.
+    pub fn move_next(&mut self) {
+        self.pos.move_next(self.tree.root.as_ref());
+    }
+
+    /// Move to the next smaller value.
+    ///
+    /// From the minimum value, move to the ghost position; from the ghost position, move to the maximum value.
+    pub fn move_prev(&mut self) {
+        self.pos.move_prev(self.tree.root.as_ref());
+    }
+}
+
+/// Cursor over the distinct values of AVL tree which can modify the tree.
+pub struct CursorMut<'a, T> {
+    tree: &'a mut AvlTree<T>,
+    pos: Position<T>,
+}
+
+impl<'a, T: TreeElem> CursorMut<'a, T> {
+    /// Return `(value, rank, number_of_duplicates)` of the current value,
+    /// or `None` at the ghost position.
+    pub fn current(&self) -> Option<(T, usize, usize)> {
+        self.pos.current(self.tree.root.as_ref())
+    }
+
+    /// Move to the next larger value.
+    ///
+    /// From the maximum value, move to the ghost position; from the ghost position, move to the minimum value.
+    pub fn move_next(&mut self) {
+        self.pos.move_next(self.tree.root.as_ref());
+    }
+
+    /// Move to the next smaller value.
+    ///
+    /// From the minimum value, move to the ghost position; from the ghost position, move to the maximum value.
+    pub fn move_prev(&mut self) {
+        self.pos.move_prev(self.tree.root.as_ref());
+    }
+
+    /// Push `value` and return the rank and the number of duplication of it.
+    ///
+    /// The cursor stays at the current value.
+    pub fn insert(&mut self, value: T) -> (usize, usize) {
+        let current = self.current();
+        self.pos = Position::ghost();
+        let n_ledu = self.tree.push(value);
+        if let Some((current, _, _)) = current {
+            self.pos = Position::lower_bound(self.tree.root.as_ref(), current, false);
+        }
+        n_ledu
+    }
+
+    /// Remove one of the current value and return it.
+    ///
+    /// If no duplicate is left, move to the next larger value.
+    pub fn remove_current(&mut self) -> Option<T> {
+        let (value, _, _) = self.current()?;
+        self.pos = Position::ghost();
+        self.tree.remove(value).expect("the current value is in the tree");
+        self.pos = Position::lower_bound(self.tree.root.as_ref(), value, false);
+        Some(value)
+    }
+
+    /// Remove the current value with its duplicates, return it and the number of duplicates,
+    /// then move to the next larger value.
+    pub fn remove_current_all(&mut self) -> Option<(T, usize)> {
+        let (value, _, dup) = self.current()?;
+        self.pos = Position::ghost();
+        self.tree.remove_n(value, dup + 1);
+        self.pos = Position::lower_bound(self.tree.root.as_ref(), value, false);
+        Some((value, dup))
+    }
+
+    /// Return the tree as read-only.
+    pub fn as_tree(&self) -> &AvlTree<T> {
+        self.tree
+    }
+}
+
+impl<T: TreeElem> AvlTree<T> {
+    /// Return a cursor at the minimum value.
+    pub fn cursor_first(&self) -> Cursor<'_, T> {
+        Cursor { pos: Position::first(self.root.as_ref()), tree: self }
+    }
+
+    /// Return a cursor at the maximum value.
+    pub fn cursor_last(&self) -> Cursor<'_, T> {
+        Cursor { pos: Position::last(self.root.as_ref()), tree: self }
+    }
+
+    /// Return a cursor at the smallest value larger than or equal to `value`.
+    ///
+    /// If there is no such value, the cursor is at the ghost position.
+    pub fn lower_bound_cursor(&self, value: T) -> Cursor<'_, T> {
+        Cursor { pos: Position::lower_bound(self.root.as_ref(), value, false), tree: self }
+    }
+
+    /// Return a cursor at the smallest value larger than `value`.
+    ///
+    /// If there is no such value, the cursor is at the ghost position.
+    pub fn upper_bound_cursor(&self, value: T) -> Cursor<'_, T> {
+        Cursor { pos: Position::lower_bound(self.root.as_ref(), value, true), tree: self }
+    }
+
+    /// Return a cursor at the value of the `k`-th smallest element (0-indexed).
+    ///
+    /// If `k` is out of range, the cursor is at the ghost position.
+    pub fn nth_cursor(&self, k: usize) -> Cursor<'_, T> {
+        Cursor { pos: Position::nth(self.root.as_ref(), k), tree: self }
+    }
+
+    /// Return a mutable cursor at the minimum value.
+    pub fn cursor_first_mut(&mut self) -> CursorMut<'_, T> {
+        CursorMut { pos: Position::first(self.root.as_ref()), tree: self }
+    }
+
+    /// Return a mutable cursor at the maximum value.
+    pub fn cursor_last_mut(&mut self) -> CursorMut<'_, T> {
+        CursorMut { pos: Position::last(self.root.as_ref()), tree: self }
+    }
+
+    /// Return a mutable cursor at the smallest value larger than or equal to `value`.
+    ///
+    /// If there is no such value, the cursor is at the ghost position.
+    pub fn lower_bound_cursor_mut(&mut self, value: T) -> CursorMut<'_, T> {
+        CursorMut { pos: Position::lower_bound(self.root.as_ref(), value, false), tree: self }
+    }
+
+    /// Return a mutable cursor at the smallest value larger than `value`.
+    ///
+    /// If there is no such value, the cursor is at the ghost position.
+    pub fn upper_bound_cursor_mut(&mut self, value: T) -> CursorMut<'_, T> {
+        CursorMut { pos: Position::lower_bound(self.root.as_ref(), value, true), tree: self }
+    }
+
+    /// Return a mutable cursor at the value of the `k`-th smallest element (0-indexed).
+    ///
+    /// If `k` is out of range, the cursor is at the ghost position.
+    pub fn nth_cursor_mut(&mut self, k: usize) -> CursorMut<'_, T> {
+        CursorMut { pos: Position::nth(self.root.as_ref(), k), tree: self }
+    }
+}
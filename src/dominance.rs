@@ -0,0 +1,101 @@
+//! Two-dimensional dominance counting.
+//!
+//! A point `(x', y')` is dominated by `(x, y)` if `x' <= x` and `y' <= y`.
+//! Every point dominates itself.
+
+use std::cmp::Ordering;
+
+use crate::sort::{argsort, sort_dedup_counts};
+use crate::traits::{cmp_elem, is_comparable, TreeElem};
+use crate::tree::AvlTree;
+
+/// Return the number of points dominated by each point of `points`, including itself.
+///
+/// The points are swept in ascending order of `x` with AVL tree over `y` in `O(n log n)`.
+///
+/// # Panics
+///
+/// Panics if a coordinate is NaN.
+pub fn dominance_counts<T: TreeElem, U: TreeElem>(points: &[(T, U)]) -> Vec<usize> {
+    // Sort the points by `x`, then by `y`.
+    let ys: Vec<U> = points.iter().map(|&(_, y)| y).collect();
+    let by_y = argsort(&ys);
+    let x_by_y: Vec<T> = by_y.iter().map(|&i| points[i].0).collect();
+    let order: Vec<usize> = argsort(&x_by_y).into_iter().map(|i| by_y[i]).collect();
+
+    let mut tree = AvlTree::new();
+    let mut counts = vec![0; points.len()];
+    for &i in order.iter() {
+        let (rank, dup) = tree.push(points[i].1);
+        counts[i] = rank + dup + 1;
+    }
+    // The same points pushed later are dominated as well.
+    for k in (1..order.len()).rev() {
+        if points[order[k - 1]] == points[order[k]] {
+            counts[order[k - 1]] = counts[order[k]];
+        }
+    }
+    counts
+}
+
+/// Index of points answering dominance counts online.
+///
+/// This is a Fenwick tree over the distinct `x` holding AVL trees over `y`,
+/// so a query takes `O(log^2 n)` and the index takes `O(n log n)` memory.
+pub struct DominanceIndex<T, U> {
+    /// The distinct `x` in ascending order.
+    xs: Vec<T>,
+    /// The cells of the Fenwick tree, 1-indexed by the position in `xs`.
+    cells: Vec<AvlTree<U>>,
+}
+
+impl<T: TreeElem, U: TreeElem> DominanceIndex<T, U> {
+    /// Create an index of `points`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a coordinate is NaN.
+    pub fn new(points: &[(T, U)]) -> Self {
+        assert!(points.iter().all(|(_, y)| is_comparable(y)), "NaN cannot be indexed");
+        let all_xs: Vec<T> = points.iter().map(|&(x, _)| x).collect();
+        let xs: Vec<T> = sort_dedup_counts(&all_xs).into_iter().map(|(x, _)| x).collect();
+        let mut cells: Vec<AvlTree<U>> = (0..xs.len()).map(|_| AvlTree::new()).collect();
+        for &(x, y) in points.iter() {
+            let mut i = xs.partition_point(|&v| cmp_elem(v, x) == Ordering::Less) + 1;
+            while i <= cells.len() {
+                cells[i - 1].push(y);
+                i += i & i.wrapping_neg();
+            }
+        }
+        Self { xs, cells }
+    }
+
+    /// Count the points dominated by `(x, y)`.
+    ///
+    /// `(x, y)` does not need to be in the index.
+    pub fn count(&self, x: T, y: U) -> usize {
+        let mut i = self.xs.partition_point(|&v| cmp_elem(v, x) != Ordering::Greater);
+        let mut count = 0;
+        while i > 0 {
+            count += self.cells[i - 1].rank_upper(y);
+            i -= i & i.wrapping_neg();
+        }
+        count
+    }
+
+    /// Return the number of points.
+    pub fn len(&self) -> usize {
+        let mut i = self.cells.len();
+        let mut len = 0;
+        while i > 0 {
+            len += self.cells[i - 1].len();
+            i -= i & i.wrapping_neg();
+        }
+        len
+    }
+
+    /// Determine if the index has no points.
+    pub fn is_empty(&self) -> bool {
+        self.xs.is_empty()
+    }
+}
@@ -0,0 +1,266 @@
+//! Entries of AVL tree for in-place count manipulation.
+//!
+//! An occupied entry keeps the path from the root to the node of its value,
+//! so pushing or removing copies of the value only updates the counts along the path.
+//! Inserting a new value or removing the last copy of it changes the shape of the tree,
+//! so the value is searched again after them, as well as after an eviction.
+
+use std::cmp::Ordering;
+use std::sync::{Arc, Mutex};
+
+use crate::node::AvlNode;
+use crate::traits::{cmp_elem, TreeElem};
+use crate::tree::{AvlTree, Evict};
+
+/// Entry of a value in AVL tree, which may or may not exist.
+pub enum Entry<'a, T> {
+    /// The value exists in the tree.
+    Occupied(OccupiedEntry<'a, T>),
+    /// The value does not exist in the tree.
+    Vacant(VacantEntry<'a, T>),
+}
+
+/// Entry of a value which exists in the tree.
+pub struct OccupiedEntry<'a, T> {
+    tree: &'a mut AvlTree<T>,
+    value: T,
+    rank: usize,
+    count: usize,
+    /// Nodes from the child of the root to the node of the value, and whether each of them is a left child.
+    /// If it is empty, the node of the value is the root.
+    path: Vec<(Arc<Mutex<AvlNode<T>>>, bool)>,
+}
+
+/// Entry of a value which does not exist in the tree.
+pub struct VacantEntry<'a, T> {
+    tree: &'a mut AvlTree<T>,
+    value: T,
+    rank: usize,
+}
+
+impl<'a, T: TreeElem> Entry<'a, T> {
+    /// Search `value` in `tree` and create the entry of it.
+    fn new(tree: &'a mut AvlTree<T>, value: T) -> Self {
+        let mut path = Vec::new();
+        let mut rank = 0;
+        let mut dup = None;
+        if let Some(r) = &tree.root {
+            let mut step = |n: &AvlNode<T>| {
+                match cmp_elem(value, n.value) {
+                    Ordering::Equal => {
+                        rank += n.n_ledu.0;
+                        dup = Some(n.n_ledu.1);
+                        None
+                    }
+                    Ordering::Less => n.left.clone().map(|node| (node, true)),
+                    Ordering::Greater => {
+                        rank += n.n_ledu.0 + n.n_ledu.1 + 1;
+                        n.right.clone().map(|node| (node, false))
+                    }
+                }
+            };
+            let mut next = step(r);
+            while let Some((node, is_left)) = next {
+                next = step(&node.lock().unwrap());
+                path.push((node, is_left));
+            }
+        }
+        match dup {
+            Some(dup) => Entry::Occupied(OccupiedEntry { tree, value, rank, count: dup + 1, path }),
+            None => Entry::Vacant(VacantEntry { tree, value, rank }),
+        }
+    }
+
+    /// Return the value of the entry.
+    pub fn value(&self) -> T {
+        match self {
+            Entry::Occupied(e) => e.value,
+            Entry::Vacant(e) => e.value,
+        }
+    }
+
+    /// Return the number of the value in the tree.
+    pub fn count(&self) -> usize {
+        match self {
+            Entry::Occupied(e) => e.count,
+            Entry::Vacant(_) => 0,
+        }
+    }
+
+    /// Return the number of elements smaller than the value.
+    pub fn rank(&self) -> usize {
+        match self {
+            Entry::Occupied(e) => e.rank,
+            Entry::Vacant(e) => e.rank,
+        }
+    }
+
+    /// Push one of the value.
+    pub fn insert(self) -> Self {
+        self.increment(1)
+    }
+
+    /// Push `n` of the value.
+    ///
+    /// If the tree has a capacity limit, elements may be evicted, including the value itself.
+    pub fn increment(self, n: usize) -> Self {
+        match self {
+            Entry::Occupied(e) => e.increment(n),
+            Entry::Vacant(e) => e.insert_n(n),
+        }
+    }
+
+    /// Remove `n` of the value.
+    ///
+    /// If there are `n` or fewer of the value, remove all of them and the entry becomes vacant.
+    pub fn decrement(self, n: usize) -> Self {
+        match self {
+            Entry::Occupied(e) => e.decrement(n),
+            Entry::Vacant(e) => Entry::Vacant(e),
+        }
+    }
+}
+
+impl<'a, T: TreeElem> OccupiedEntry<'a, T> {
+    /// Return the value of the entry.
+    pub fn value(&self) -> T {
+        self.value
+    }
+
+    /// Return the number of the value in the tree.
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    /// Return the number of elements smaller than the value.
+    pub fn rank(&self) -> usize {
+        self.rank
+    }
+
+    /// Push `n` of the value.
+    ///
+    /// If the tree has a capacity limit, elements may be evicted, including the value itself.
+    pub fn increment(mut self, n: usize) -> Entry<'a, T> {
+        if n == 0 {
+            return Entry::Occupied(self);
+        }
+        self.update_path(|count| *count += n);
+        self.tree.check(format_args!("increment({}, {})", self.value, n));
+        if evict(self.tree) {
+            return Entry::new(self.tree, self.value);
+        }
+        let count = self.count + n;
+        Entry::Occupied(Self { count, ..self })
+    }
+
+    /// Remove `n` of the value.
+    ///
+    /// If there are `n` or fewer of the value, remove all of them and the entry becomes vacant.
+    pub fn decrement(mut self, n: usize) -> Entry<'a, T> {
+        if n < self.count {
+            self.update_path(|count| *count -= n);
+            self.tree.check(format_args!("decrement({}, {})", self.value, n));
+            let count = self.count - n;
+            Entry::Occupied(Self { count, ..self })
+        } else {
+            self.remove().0
+        }
+    }
+
+    /// Apply `f` to the number of duplicates of the value,
+    /// and to the number of smaller elements of the ancestors of which the value is in the left subtree.
+    fn update_path<F: Fn(&mut usize)>(&mut self, f: F) {
+        // Go up from the node of the value, locking one node at a time.
+        let mut from_left = None;
+        for (node, is_left) in self.path.iter().rev() {
+            let mut n = node.lock().unwrap();
+            match from_left {
+                Some(true) => f(&mut n.n_ledu.0),
+                Some(false) => {}
+                None => f(&mut n.n_ledu.1),
+            }
+            n.update();
+            from_left = Some(*is_left);
+        }
+        let r = self.tree.root.as_mut().unwrap();
+        match from_left {
+            Some(true) => f(&mut r.n_ledu.0),
+            Some(false) => {}
+            None => f(&mut r.n_ledu.1),
+        }
+        r.update();
+    }
+
+    /// Remove all of the value and return the vacant entry and the number of removed elements.
+    ///
+    /// Removing the node rebalances the tree, so the value is searched again.
+    pub fn remove(self) -> (Entry<'a, T>, usize) {
+        let OccupiedEntry { tree, value, rank, count, .. } = self;
+        let removed = tree.remove_n(value, count);
+        (Entry::Vacant(VacantEntry { tree, value, rank }), removed)
+    }
+}
+
+impl<'a, T: TreeElem> VacantEntry<'a, T> {
+    /// Return the value of the entry.
+    pub fn value(&self) -> T {
+        self.value
+    }
+
+    /// Return the number of elements smaller than the value.
+    pub fn rank(&self) -> usize {
+        self.rank
+    }
+
+    /// Push one of the value.
+    ///
+    /// If the tree has a capacity limit, elements may be evicted, including the value itself.
+    pub fn insert(self) -> Entry<'a, T> {
+        self.insert_n(1)
+    }
+
+    /// Push `n` of the value.
+    ///
+    /// If the tree has a capacity limit, elements may be evicted, including the value itself.
+    pub fn insert_n(self, n: usize) -> Entry<'a, T> {
+        if n == 0 {
+            return Entry::Vacant(self);
+        }
+        match &mut self.tree.root {
+            Some(r) => {
+                let _ = r.push_n_child(self.value, n);
+            }
+            None => self.tree.root = Some(AvlNode::with_count(self.value, n)),
+        }
+        self.tree.check(format_args!("insert_n({}, {})", self.value, n));
+        // The insertion may rotate the nodes, so the path to the value is searched again.
+        let VacantEntry { tree, value, .. } = self;
+        evict(tree);
+        Entry::new(tree, value)
+    }
+}
+
+/// Evict elements while `tree` exceeds its capacity limit, and return whether some elements are evicted.
+fn evict<T: TreeElem>(tree: &mut AvlTree<T>) -> bool {
+    let (limit, evict) = match tree.capacity_limit() {
+        Some(limit) => limit,
+        None => return false,
+    };
+    let mut len = tree.len();
+    let evicted = len > limit;
+    while len > limit {
+        let _ = match evict {
+            Evict::Min => tree.pop_min(),
+            Evict::Max => tree.pop_max(),
+        };
+        len -= 1;
+    }
+    evicted
+}
+
+impl<T: TreeElem> AvlTree<T> {
+    /// Return the entry of `value`.
+    pub fn entry(&mut self, value: T) -> Entry<'_, T> {
+        Entry::new(self, value)
+    }
+}
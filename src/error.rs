@@ -0,0 +1,162 @@
+//! Errors of AVL tree and its binary format.
+
+use std::fmt;
+
+use crate::node::Direction;
+
+/// Errors of AVL tree.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AvlError {
+    /// The value does not exist in the tree.
+    NotFound,
+}
+
+impl fmt::Display for AvlError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AvlError::NotFound => write!(f, "the value does not exist in the tree"),
+        }
+    }
+}
+
+impl std::error::Error for AvlError {}
+
+/// Errors of decoding AVL tree from the binary format.
+#[derive(Debug)]
+pub enum DecodeError {
+    /// The input could not be read.
+    Io(std::io::Error),
+    /// The input ended before the tree.
+    Truncated,
+    /// The input does not start with the magic bytes.
+    BadMagic,
+    /// The version of the format is not supported.
+    UnsupportedVersion(u8),
+    /// The element type of the input differs from that of the tree.
+    TypeMismatch {
+        /// The type tag of the tree.
+        expected: u8,
+        /// The type tag of the input.
+        found: u8,
+    },
+    /// The values are not strictly ascending at the index.
+    Unsorted(u64),
+    /// The number of a value is zero at the index.
+    ZeroCount(u64),
+    /// A number in the input does not fit in `isize` or `usize`, or the total number of elements overflows `usize`.
+    Overflow,
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DecodeError::Io(e) => write!(f, "failed to read the input: {}", e),
+            DecodeError::Truncated => write!(f, "the input is truncated"),
+            DecodeError::BadMagic => write!(f, "the input is not an AVL tree"),
+            DecodeError::UnsupportedVersion(v) => write!(f, "unsupported version: {}", v),
+            DecodeError::TypeMismatch { expected, found } => {
+                write!(f, "element type mismatch: expected {}, found {}", expected, found)
+            }
+            DecodeError::Unsorted(i) => write!(f, "the values are not strictly ascending at {}", i),
+            DecodeError::ZeroCount(i) => write!(f, "the number of the value is zero at {}", i),
+            DecodeError::Overflow => write!(f, "a number in the input is out of range"),
+        }
+    }
+}
+
+impl std::error::Error for DecodeError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            DecodeError::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for DecodeError {
+    fn from(e: std::io::Error) -> Self {
+        if e.kind() == std::io::ErrorKind::UnexpectedEof {
+            DecodeError::Truncated
+        } else {
+            DecodeError::Io(e)
+        }
+    }
+}
+
+/// Invariants of AVL tree which can be broken at a node.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Violation {
+    /// The value is out of the range given by the ancestors.
+    Order,
+    /// `diff` is out of `[-1, 1]`.
+    Unbalanced {
+        /// `diff` of the node.
+        diff: i32,
+    },
+    /// `diff` differs from the actual difference of heights of children.
+    Diff {
+        /// `diff` of the node.
+        diff: i32,
+        /// The height of the left child minus that of the right child.
+        actual: i32,
+    },
+    /// `n_ledu.0` differs from the number of elements in the left child.
+    LessCount {
+        /// `n_ledu.0` of the node.
+        n_less: usize,
+        /// The number of elements in the left child.
+        actual: usize,
+    },
+    /// `max_dup` differs from the maximum number of duplicates in the tree at the bottom.
+    MaxDup {
+        /// `max_dup` of the node.
+        max_dup: usize,
+        /// The maximum of `n_ledu.1` in the tree at the bottom.
+        actual: usize,
+    },
+    /// `(len, n_nodes, height)` differ from the actual numbers of elements and nodes and the height.
+    Cached {
+        /// `(len, n_nodes, height)` of the node.
+        cached: (usize, usize, usize),
+        /// The actual numbers of elements and nodes and the height of the tree at the bottom.
+        actual: (usize, usize, usize),
+    },
+    /// The node is shared with another position of the tree.
+    Aliased,
+}
+
+/// Report of the first broken invariant of AVL tree.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct InvariantReport {
+    /// The path from the root to the offending node.
+    pub path: Vec<Direction>,
+    /// The broken invariant.
+    pub violation: Violation,
+}
+
+impl fmt::Display for InvariantReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invariant broken at root")?;
+        for dir in self.path.iter() {
+            match dir {
+                Direction::Left => write!(f, "/L")?,
+                Direction::Right => write!(f, "/R")?,
+            }
+        }
+        match self.violation {
+            Violation::Order => write!(f, ": the value is out of order"),
+            Violation::Unbalanced { diff } => write!(f, ": diff={} is out of [-1, 1]", diff),
+            Violation::Diff { diff, actual } => write!(f, ": diff={}, but the actual difference is {}", diff, actual),
+            Violation::LessCount { n_less, actual } => {
+                write!(f, ": n_ledu.0={}, but the left child has {} elements", n_less, actual)
+            }
+            Violation::MaxDup { max_dup, actual } => write!(f, ": max_dup={}, but the actual maximum is {}", max_dup, actual),
+            Violation::Cached { cached, actual } => {
+                write!(f, ": (len, n_nodes, height)={:?}, but the actual values are {:?}", cached, actual)
+            }
+            Violation::Aliased => write!(f, ": the node is shared"),
+        }
+    }
+}
+
+impl std::error::Error for InvariantReport {}
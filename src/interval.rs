@@ -0,0 +1,178 @@
+//! Interval tree over AVL tree.
+//!
+//! Intervals are closed, `[lo, hi]`, and keyed by `lo`.
+//! Each node keeps the maximum `hi` in the tree at the bottom to prune the searches.
+
+use std::cmp::Ordering;
+use std::iter::FromIterator;
+
+use crate::node::AvlNode;
+use crate::traits::{NodeData, TreeElem};
+use crate::tree::AvlTree;
+
+/// The ends of the intervals starting at a node, and the maximum end in the tree at the bottom.
+struct Ends<T> {
+    his: Vec<T>,
+    max_hi: Option<T>,
+}
+
+impl<T> Default for Ends<T> {
+    fn default() -> Self {
+        Self { his: Vec::new(), max_hi: None }
+    }
+}
+
+impl<T: TreeElem> NodeData for Ends<T> {
+    fn refresh(&mut self, left: Option<&Self>, right: Option<&Self>) {
+        let mut max_hi = None;
+        let children = left.and_then(|ends| ends.max_hi).into_iter().chain(right.and_then(|ends| ends.max_hi));
+        for hi in self.his.iter().copied().chain(children) {
+            match max_hi {
+                Some(m) if m >= hi => {}
+                _ => max_hi = Some(hi),
+            }
+        }
+        self.max_hi = max_hi;
+    }
+
+    fn count(&self) -> Option<usize> {
+        Some(self.his.len())
+    }
+}
+
+/// Interval tree of closed intervals over AVL tree.
+pub struct AvlIntervalTree<T> {
+    /// Root node keyed by `lo`. The number of duplicates of a node is derived from the number of its intervals.
+    root: Option<AvlNode<T, Ends<T>>>,
+    /// The ends of all the intervals to count overlapping intervals.
+    his: AvlTree<T>,
+}
+
+impl<T: TreeElem> Default for AvlIntervalTree<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Collect the intervals `[lo, hi]` from the pairs `(lo, hi)`.
+impl<T: TreeElem> FromIterator<(T, T)> for AvlIntervalTree<T> {
+    fn from_iter<I: IntoIterator<Item = (T, T)>>(iter: I) -> Self {
+        let mut tree = Self::new();
+        for (lo, hi) in iter {
+            tree.insert(lo, hi);
+        }
+        tree
+    }
+}
+
+impl<T: TreeElem> AvlIntervalTree<T> {
+    /// Create an empty interval tree.
+    pub fn new() -> Self {
+        Self { root: None, his: AvlTree::new() }
+    }
+
+    /// Insert the interval `[lo, hi]`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `lo > hi` or they are not comparable.
+    pub fn insert(&mut self, lo: T, hi: T) {
+        assert!(lo <= hi, "the interval is empty: [{}, {}]", lo, hi);
+        let ends = Ends { his: vec![hi], max_hi: Some(hi) };
+        match &mut self.root {
+            Some(r) => {
+                // The number of duplicates is derived from the ends, so the count returned here is ignored.
+                let _ = r.push_data_child(lo, 1, ends, |data, mut ends| {
+                    data.his.append(&mut ends.his);
+                    0
+                });
+            }
+            None => self.root = Some(AvlNode::with_data(lo, 1, ends)),
+        }
+        self.his.push(hi);
+    }
+
+    /// Remove one of the interval `[lo, hi]` and return the result.
+    #[allow(clippy::result_unit_err)]
+    pub fn remove(&mut self, lo: T, hi: T) -> Result<(), ()> {
+        let r = self.root.as_mut().ok_or(())?;
+        let (_, d_diff) = r.remove_with_data_child(lo, |ends| {
+            let i = ends.his.iter().position(|&h| h == hi)?;
+            ends.his.swap_remove(i);
+            Some(1)
+        })?;
+        if d_diff.is_none() {
+            self.root = None;
+        }
+        self.his.remove(hi)
+    }
+
+    /// Return the intervals containing `point` in ascending order of `lo`.
+    pub fn stabbing(&self, point: T) -> std::vec::IntoIter<(T, T)> {
+        self.overlapping(point, point)
+    }
+
+    /// Return the intervals overlapping `[lo, hi]` in ascending order of `lo`.
+    /// 
+    /// If `lo > hi`, no interval overlaps.
+    pub fn overlapping(&self, lo: T, hi: T) -> std::vec::IntoIter<(T, T)> {
+        let mut v = Vec::new();
+        if is_empty(lo, hi) {
+            return v.into_iter();
+        }
+        if let Some(r) = &self.root {
+            overlapping_child(r, lo, hi, &mut v);
+        }
+        v.into_iter()
+    }
+
+    /// Count the intervals overlapping `[lo, hi]` in `O(log n)`.
+    ///
+    /// An interval `[l, h]` overlaps unless `l > hi` or `h < lo`, and at most one of them holds if `lo <= hi`.
+    pub fn count_overlapping(&self, lo: T, hi: T) -> usize {
+        if is_empty(lo, hi) {
+            return 0;
+        }
+        let n_le = match &self.root {
+            Some(r) => r.rank_upper_child(hi),
+            None => 0,
+        };
+        n_le - self.his.rank(lo)
+    }
+
+    /// Return the number of intervals.
+    pub fn len(&self) -> usize {
+        self.his.len()
+    }
+
+    /// Determine if the interval tree is empty.
+    pub fn is_empty(&self) -> bool {
+        self.root.is_none()
+    }
+}
+
+/// Determine if `[lo, hi]` is empty.
+fn is_empty<T: TreeElem>(lo: T, hi: T) -> bool {
+    !matches!(lo.partial_cmp(&hi), Some(Ordering::Less) | Some(Ordering::Equal))
+}
+
+/// Push the intervals overlapping `[lo, hi]` in the tree at `node` to `out` in ascending order of `lo`.
+fn overlapping_child<T: TreeElem>(node: &AvlNode<T, Ends<T>>, lo: T, hi: T, out: &mut Vec<(T, T)>) {
+    match node.data.max_hi {
+        Some(max_hi) if max_hi >= lo => {}
+        _ => return,
+    }
+    if let Some(left) = &node.left {
+        overlapping_child(&left.lock().unwrap(), lo, hi, out);
+    }
+    if node.value <= hi {
+        for &h in node.data.his.iter() {
+            if h >= lo {
+                out.push((node.value, h));
+            }
+        }
+        if let Some(right) = &node.right {
+            overlapping_child(&right.lock().unwrap(), lo, hi, out);
+        }
+    }
+}
@@ -19,7 +19,7 @@
 //!        println!("value={}| rank={}, dup={}", i, rank, dup);
 //!    }
 //!    println!("Elements in the tree = {}", g.len());
-//!    println!("");
+//!    println!();
 //!
 //!    println!("Height of the tree = {}", g.height());
 //!    println!("How many 4?: {}", g.count(4));
@@ -27,23 +27,37 @@
 //!    println!("4 is in?: {}", g.isin(4));
 //!    println!("remove 4: {:?}", g.remove(4));
 //!    println!("4 is in?: {}", g.isin(4));
-//!    println!("");
+//!    println!();
 //!
 //!    println!("Max value = {}", g.max().unwrap());
 //!    let (value, dup) = g.pop_max_all().unwrap();
 //!    println!("Pop all the max value: value={}, dup={}", value, dup);
-//!    println!("");
+//!    println!();
 //!
 //!    println!("=== Show elements in ascending order. ===");
-//!    loop {
-//!        match g.pop_min() {
-//!            Some(value) => println!("Min value = {}", value),
-//!            None => break,
-//!        }
+//!    while let Some(value) = g.pop_min() {
+//!        println!("Min value = {}", value);
 //!    }
 //! }
 //! ```
 
+pub mod binary;
+pub mod cursor;
+pub mod dominance;
+pub mod entry;
+pub mod error;
+pub mod interval;
+pub mod lis;
+pub mod map;
 pub mod node;
+pub mod rank;
+pub mod seq;
+#[cfg(feature = "serde")]
+mod serde_impl;
+pub mod sort;
+pub mod stats;
 pub mod traits;
-pub mod tree;
\ No newline at end of file
+pub mod tree;
+pub mod window;
+
+pub use sort::{argsort, sort, sort_by_key, sort_dedup_counts};
\ No newline at end of file
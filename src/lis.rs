@@ -0,0 +1,135 @@
+//! Longest increasing subsequence by patience sorting.
+//!
+//! The tops of the piles are kept in AVL tree,
+//! and the pile of an element is found by the lower or upper bound of it.
+
+use std::cmp::Ordering;
+
+use crate::traits::{cmp_elem, is_comparable, TreeElem};
+use crate::tree::AvlTree;
+
+/// Piles of patience sorting.
+struct Piles {
+    /// The pile of each element.
+    pile: Vec<usize>,
+    /// The top of the previous pile when each element is placed.
+    prev: Vec<Option<usize>>,
+    /// The index of the top of each pile.
+    tops: Vec<usize>,
+}
+
+impl Piles {
+    /// Place the elements of `v` on the piles.
+    ///
+    /// If `strict`, an element is placed on the leftmost pile whose top is larger than or equal to it,
+    /// otherwise larger than it.
+    fn new<T: TreeElem>(v: &[T], strict: bool) -> Self {
+        assert!(v.iter().all(is_comparable), "NaN cannot be ordered");
+        let mut top_values = AvlTree::new();
+        let mut piles = Self { pile: Vec::with_capacity(v.len()), prev: Vec::with_capacity(v.len()), tops: Vec::new() };
+        for (i, &value) in v.iter().enumerate() {
+            let bound = if strict {
+                top_values.lower_bound_cursor(value).current()
+            } else {
+                top_values.upper_bound_cursor(value).current()
+            };
+            let p = match bound {
+                Some((top, rank, _)) => {
+                    top_values.replace(top, value).unwrap();
+                    piles.tops[rank] = i;
+                    rank
+                }
+                None => {
+                    top_values.push(value);
+                    piles.tops.push(i);
+                    piles.tops.len() - 1
+                }
+            };
+            piles.pile.push(p);
+            piles.prev.push(if p > 0 { Some(piles.tops[p - 1]) } else { None });
+        }
+        piles
+    }
+
+    /// Return the indices of a longest subsequence.
+    fn indices(&self) -> Vec<usize> {
+        let mut indices = Vec::with_capacity(self.tops.len());
+        let mut i = self.tops.last().copied();
+        while let Some(j) = i {
+            indices.push(j);
+            i = self.prev[j];
+        }
+        indices.reverse();
+        indices
+    }
+}
+
+/// Return the length of the longest strictly increasing subsequence of `v`.
+///
+/// # Panics
+///
+/// Panics if `v` has NaN.
+pub fn lis_length<T: TreeElem>(v: &[T]) -> usize {
+    Piles::new(v, true).tops.len()
+}
+
+/// Return the indices of a longest strictly increasing subsequence of `v` in ascending order.
+///
+/// # Panics
+///
+/// Panics if `v` has NaN.
+pub fn lis_indices<T: TreeElem>(v: &[T]) -> Vec<usize> {
+    Piles::new(v, true).indices()
+}
+
+/// Return the length of the longest non-decreasing subsequence of `v`.
+///
+/// # Panics
+///
+/// Panics if `v` has NaN.
+pub fn lnds_length<T: TreeElem>(v: &[T]) -> usize {
+    Piles::new(v, false).tops.len()
+}
+
+/// Return the indices of a longest non-decreasing subsequence of `v` in ascending order.
+///
+/// # Panics
+///
+/// Panics if `v` has NaN.
+pub fn lnds_indices<T: TreeElem>(v: &[T]) -> Vec<usize> {
+    Piles::new(v, false).indices()
+}
+
+/// Count the longest strictly increasing subsequences of `v` by their indices.
+///
+/// The count saturates at `u64::MAX`, and is zero if `v` is empty.
+///
+/// # Panics
+///
+/// Panics if `v` has NaN.
+pub fn lis_count<T: TreeElem>(v: &[T]) -> u64 {
+    if v.is_empty() {
+        return 0;
+    }
+    let piles = Piles::new(v, true);
+    // The values placed on each pile, which are non-increasing,
+    // and the cumulative sums of the numbers of the subsequences ending at them.
+    // Since each number is saturated at `u64::MAX`, the sums do not overflow `u128`.
+    let mut values: Vec<Vec<T>> = vec![Vec::new(); piles.tops.len()];
+    let mut sums: Vec<Vec<u128>> = vec![vec![0]; piles.tops.len()];
+    for (&value, &p) in v.iter().zip(piles.pile.iter()) {
+        let count = if p == 0 {
+            1
+        } else {
+            // The elements of the previous pile smaller than `value` are at the end.
+            let start = values[p - 1].partition_point(|&prev| cmp_elem(prev, value) != Ordering::Less);
+            let prev_sums = &sums[p - 1];
+            (prev_sums[prev_sums.len() - 1] - prev_sums[start]).min(u64::MAX as u128)
+        };
+        values[p].push(value);
+        let last = sums[p][sums[p].len() - 1];
+        sums[p].push(last + count);
+    }
+    let last = &sums[piles.tops.len() - 1];
+    last[last.len() - 1].min(u64::MAX as u128) as u64
+}
@@ -0,0 +1,300 @@
+//! Maps over AVL tree with order statistics of keys.
+//!
+//! The values are attached to the nodes of the tree, so they do not need to implement `TreeElem`.
+//! Since the nodes are behind `Mutex`, values are returned by cloning them,
+//! or accessed in closures by `get_with` and `get_mut_with`.
+//! The iterators visit the nodes lazily, and lock each node only while cloning its values.
+
+use std::ops::{RangeBounds, RangeFull};
+
+use crate::node::{AvlNode, RangeNodes};
+use crate::traits::TreeElem;
+
+/// Iterator over the keys in a range of `AvlMap` and the values at them in ascending order of keys.
+pub struct MapRange<'a, K, V, R> {
+    nodes: RangeNodes<'a, K, Option<V>, R>,
+}
+
+impl<'a, K: TreeElem, V: Clone, R: RangeBounds<K>> Iterator for MapRange<'a, K, V, R> {
+    type Item = (K, V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.nodes.next()?;
+        let n = node.lock();
+        Some((n.value, n.data.clone().unwrap()))
+    }
+}
+
+/// Map from keys to values over AVL tree.
+pub struct AvlMap<K, V> {
+    /// Root node. The data of every node is `Some`.
+    pub(crate) root: Option<AvlNode<K, Option<V>>>,
+}
+
+impl<K: TreeElem, V> Default for AvlMap<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: TreeElem, V> AvlMap<K, V> {
+    /// Create an empty map.
+    pub fn new() -> Self {
+        Self { root: None }
+    }
+
+    /// Insert `value` at `key` and return the old value at `key`.
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        match &mut self.root {
+            Some(r) => {
+                let mut old = None;
+                let _ = r.push_data_child(key, 1, Some(value), |data, value| {
+                    old = std::mem::replace(data, value);
+                    0
+                });
+                old
+            }
+            None => {
+                self.root = Some(AvlNode::with_data(key, 1, Some(value)));
+                None
+            }
+        }
+    }
+
+    /// Determine if `key` exists.
+    pub fn contains_key(&self, key: K) -> bool {
+        match &self.root {
+            Some(r) => r.search(key).is_some(),
+            None => false,
+        }
+    }
+
+    /// Return `f(value_at_key)`.
+    pub fn get_with<R, F: FnOnce(&V) -> R>(&self, key: K, f: F) -> Option<R> {
+        match &self.root {
+            Some(r) => r.search_with_child(key, |node| f(node.data.as_ref().unwrap())),
+            None => None,
+        }
+    }
+
+    /// Return `f(value_at_key)`, where `f` may modify the value.
+    pub fn get_mut_with<R, F: FnOnce(&mut V) -> R>(&mut self, key: K, f: F) -> Option<R> {
+        match &mut self.root {
+            Some(r) => r.search_mut_child(key, |node| f(node.data.as_mut().unwrap())),
+            None => None,
+        }
+    }
+
+    /// Remove `key` and return the value at it.
+    pub fn remove(&mut self, key: K) -> Option<V> {
+        let r = self.root.as_mut()?;
+        let (_, _, data, d_diff) = r.remove_n_data_child(key, 1).ok()?;
+        if d_diff.is_none() {
+            self.root = None;
+        }
+        data.flatten()
+    }
+
+    /// Return the number of keys smaller than `key`.
+    ///
+    /// `key` does not need to exist in the map.
+    pub fn rank(&self, key: K) -> usize {
+        match &self.root {
+            Some(r) => r.rank_child(key),
+            None => 0,
+        }
+    }
+
+    /// Return the number of keys.
+    pub fn len(&self) -> usize {
+        match &self.root {
+            Some(r) => r.len_child_and_self(),
+            None => 0,
+        }
+    }
+
+    /// Determine if the map is empty.
+    pub fn is_empty(&self) -> bool {
+        self.root.is_none()
+    }
+}
+
+impl<K: TreeElem, V: Clone> AvlMap<K, V> {
+    /// Return the value at `key`.
+    pub fn get(&self, key: K) -> Option<V> {
+        self.get_with(key, |value| value.clone())
+    }
+
+    /// Return the `k`-th smallest key (0-indexed) and the value at it.
+    pub fn nth(&self, k: usize) -> Option<(K, V)> {
+        match &self.root {
+            Some(r) => r.nth_with_child(k, |node, _| (node.value, node.data.clone().unwrap())),
+            None => None,
+        }
+    }
+
+    /// Return the keys in `range` and the values at them in ascending order of keys.
+    pub fn range<R: RangeBounds<K>>(&self, range: R) -> MapRange<'_, K, V, R> {
+        MapRange { nodes: RangeNodes::new(self.root.as_ref(), range) }
+    }
+
+    /// Return all the keys and the values in ascending order of keys.
+    pub fn iter(&self) -> MapRange<'_, K, V, RangeFull> {
+        self.range(..)
+    }
+}
+
+/// Iterator over the keys in a range of `AvlMultiMap` and the values at them,
+/// ordered by keys and then by insertion.
+pub struct MultiMapRange<'a, K, V, R> {
+    nodes: RangeNodes<'a, K, Vec<V>, R>,
+    /// The key of the current node and the rest of the values at it.
+    values: Option<(K, std::vec::IntoIter<V>)>,
+}
+
+impl<'a, K: TreeElem, V: Clone, R: RangeBounds<K>> Iterator for MultiMapRange<'a, K, V, R> {
+    type Item = (K, V);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some((key, values)) = &mut self.values {
+                if let Some(value) = values.next() {
+                    return Some((*key, value));
+                }
+            }
+            let node = self.nodes.next()?;
+            let n = node.lock();
+            self.values = Some((n.value, n.data.clone().into_iter()));
+        }
+    }
+}
+
+/// Map from keys to multiple values over AVL tree.
+///
+/// The values at a key are kept in the order of insertion,
+/// and the number of duplicates of a key is derived from the number of values at it.
+pub struct AvlMultiMap<K, V> {
+    /// Root node. The data of every node is not empty.
+    pub(crate) root: Option<AvlNode<K, Vec<V>>>,
+}
+
+impl<K: TreeElem, V> Default for AvlMultiMap<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: TreeElem, V> AvlMultiMap<K, V> {
+    /// Create an empty map.
+    pub fn new() -> Self {
+        Self { root: None }
+    }
+
+    /// Insert `value` at `key` and return the rank of `key` and the number of values already at it.
+    pub fn insert(&mut self, key: K, value: V) -> (usize, usize) {
+        match &mut self.root {
+            Some(r) => {
+                // The number of duplicates is derived from the values, so the count returned here is ignored.
+                let (n_ledu, _) = r.push_data_child(key, 1, vec![value], |data, mut values| {
+                    data.append(&mut values);
+                    0
+                });
+                n_ledu
+            }
+            None => {
+                self.root = Some(AvlNode::with_data(key, 1, vec![value]));
+                (0, 0)
+            }
+        }
+    }
+
+    /// Determine if `key` exists.
+    pub fn contains_key(&self, key: K) -> bool {
+        match &self.root {
+            Some(r) => r.search(key).is_some(),
+            None => false,
+        }
+    }
+
+    /// Count the number of values at `key`.
+    pub fn count(&self, key: K) -> usize {
+        match &self.root {
+            Some(r) => r.search(key).map_or(0, |dup| dup + 1),
+            None => 0,
+        }
+    }
+
+    /// Return `f(values_at_key)`.
+    pub fn get_with<R, F: FnOnce(&[V]) -> R>(&self, key: K, f: F) -> Option<R> {
+        match &self.root {
+            Some(r) => r.search_with_child(key, |node| f(&node.data)),
+            None => None,
+        }
+    }
+
+    /// Remove `key` and return the values at it.
+    pub fn remove(&mut self, key: K) -> Option<Vec<V>> {
+        let r = self.root.as_mut()?;
+        let (_, _, data, d_diff) = r.remove_n_data_child(key, usize::MAX).ok()?;
+        if d_diff.is_none() {
+            self.root = None;
+        }
+        data
+    }
+
+    /// Return the number of values at keys smaller than `key`.
+    ///
+    /// `key` does not need to exist in the map.
+    pub fn rank(&self, key: K) -> usize {
+        match &self.root {
+            Some(r) => r.rank_child(key),
+            None => 0,
+        }
+    }
+
+    /// Return the number of values.
+    pub fn len(&self) -> usize {
+        match &self.root {
+            Some(r) => r.len_child_and_self(),
+            None => 0,
+        }
+    }
+
+    /// Return the number of distinct keys.
+    pub fn distinct_len(&self) -> usize {
+        match &self.root {
+            Some(r) => r.n_nodes,
+            None => 0,
+        }
+    }
+
+    /// Determine if the map is empty.
+    pub fn is_empty(&self) -> bool {
+        self.root.is_none()
+    }
+}
+
+impl<K: TreeElem, V: Clone> AvlMultiMap<K, V> {
+    /// Return the values at `key`.
+    pub fn get(&self, key: K) -> Option<Vec<V>> {
+        self.get_with(key, |values| values.to_vec())
+    }
+
+    /// Return the `k`-th value (0-indexed) ordered by keys and then by insertion, and its key.
+    pub fn nth(&self, k: usize) -> Option<(K, V)> {
+        match &self.root {
+            Some(r) => r.nth_with_child(k, |node, i| (node.value, node.data[i].clone())),
+            None => None,
+        }
+    }
+
+    /// Return the keys in `range` and the values at them, ordered by keys and then by insertion.
+    pub fn range<R: RangeBounds<K>>(&self, range: R) -> MultiMapRange<'_, K, V, R> {
+        MultiMapRange { nodes: RangeNodes::new(self.root.as_ref(), range), values: None }
+    }
+
+    /// Return all the keys and the values, ordered by keys and then by insertion.
+    pub fn iter(&self) -> MultiMapRange<'_, K, V, RangeFull> {
+        self.range(..)
+    }
+}
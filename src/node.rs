@@ -1,11 +1,16 @@
 //! The node of AVL tree.
 
-use std::sync::{Arc, Mutex};
+use std::cmp::Ordering;
+use std::collections::HashSet;
+use std::ops::{Bound, Deref, RangeBounds};
+use std::sync::{Arc, Mutex, MutexGuard};
 
+use crate::error::{InvariantReport, Violation};
 use crate::traits::*;
 
 /// When propagating tree height information, 
 /// the direction of the child from which the information cames is indicated.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum Direction {
     Left,
     Right,
@@ -20,9 +25,13 @@ pub enum DeltaDiff {
 }
 
 /// The node of AVL tree.
-pub struct AvlNode<T> {
+/// 
+/// `data` is attached to `value` and moves with it when the tree is rotated.
+pub struct AvlNode<T, D = ()> {
     /// The value of element.
     pub value: T,
+    /// The data attached to `value`.
+    pub data: D,
     /// The difference of heights of children.
     /// 
     /// If diff > 0, the height of the left child is larger than that of the right child.
@@ -32,185 +41,141 @@ pub struct AvlNode<T> {
     /// 
     /// `(number_of_less, number_of_duplicates)`
     pub n_ledu: (usize, usize),
+    /// The maximum number of duplicates in the tree at the bottom.
+    pub max_dup: usize,
+    /// The number of elements in the tree at the bottom.
+    pub len: usize,
+    /// The number of nodes, i.e. distinct values, in the tree at the bottom.
+    pub n_nodes: usize,
+    /// The height of the tree at the bottom.
+    pub height: usize,
     /// Pointer to the left child node.
     pub left: Option<Arc<Mutex<Self>>>,
     /// Pointer to the right child node.
     pub right: Option<Arc<Mutex<Self>>>,
 }
 
-impl<T: TreeElem> AvlNode<T> {
+impl<T: Copy, D: NodeData> AvlNode<T, D> {
     /// Create a new node.
     pub fn new(value: T) -> Self {
-        Self { value, diff: 0, n_ledu: (0, 0), left: None, right: None }
+        Self::with_data(value, 1, D::default())
     }
 
-    /// Push `value` and propagate `((number_of_less, number_of_duplicates), height_information)` to parent node.
-    pub fn push_child(&mut self, value: T) -> ((usize, usize), DeltaDiff) {
-        if value < self.value {
-            self.n_ledu.0 += 1;
+    /// Create a new node holding `n` of `value`.
+    pub fn with_count(value: T, n: usize) -> Self {
+        Self::with_data(value, n, D::default())
+    }
+
+    /// Create a new node holding `n` of `value` with `data`.
+    pub fn with_data(value: T, n: usize, data: D) -> Self {
+        let mut node = Self {
+            value, data, diff: 0, n_ledu: (0, n - 1), max_dup: n - 1, len: n, n_nodes: 1, height: 1, left: None, right: None,
+        };
+        node.update();
+        node
+    }
+
+    /// Propagate the `k`-th smallest value (0-indexed) to parent node.
+    pub fn nth_child(&self, k: usize) -> Option<T> {
+        self.nth_with_child(k, |node, _| node.value)
+    }
+
+    /// Propagate `f(node_of_kth_value, index_in_duplicates)` to parent node.
+    pub fn nth_with_child<R, F: FnOnce(&Self, usize) -> R>(&self, k: usize, f: F) -> Option<R> {
+        if k < self.n_ledu.0 {
             match &self.left {
-                Some(node) => {
-                    let (n_ledu, d_diff) = node.lock().unwrap().push_child(value);
-                    
-                    (n_ledu, self.balance(d_diff, Direction::Left))
-                }
-                _ => {
-                    self.left = Some(Arc::new(Mutex::new(Self::new(value))));
-                    
-                    ((0, 0), self.balance(DeltaDiff::Longer, Direction::Left))
-                }
+                Some(node) => node.lock().unwrap().nth_with_child(k, f),
+                None => None,
             }
-        } else if value > self.value {
+        } else if k <= self.n_ledu.0 + self.n_ledu.1 {
+            Some(f(self, k - self.n_ledu.0))
+        } else {
             match &self.right {
-                Some(node) => {
-                    let (n_ledu, d_diff) = node.lock().unwrap().push_child(value);
-                    
-                    ((n_ledu.0 + self.n_ledu.0 + self.n_ledu.1 + 1, n_ledu.1), self.balance(d_diff, Direction::Right))
-                }
-                _ => {
-                    self.right = Some(Arc::new(Mutex::new(Self::new(value))));
-                    
-                    ((self.n_ledu.0 + self.n_ledu.1 + 1, 0), self.balance(DeltaDiff::Longer, Direction::Right))
-                }
+                Some(node) => node.lock().unwrap().nth_with_child(k - self.n_ledu.0 - self.n_ledu.1 - 1, f),
+                None => None,
             }
-        } else {
-            self.n_ledu.1 += 1;
-            (self.n_ledu, DeltaDiff::Zero)
         }
     }
 
-    /// Search `value` and propagate `number_of_duplicates` to parent node.
-    pub fn search(&self, value: T) -> Option<usize> {
-        if value == self.value {
-            Some(self.n_ledu.1)
-        } else {
-            if value < self.value {
-                match &self.left {
-                    Some(node) => node.lock().unwrap().search(value),
-                    None => None,
-                }
-            } else {
-                match &self.right {
-                    Some(node) => node.lock().unwrap().search(value),
-                    None => None,
-                }
+    /// Propagate the smallest of the most frequent values and the number of it to parent node.
+    pub fn mode_child(&self) -> (T, usize) {
+        if let Some(node) = &self.left {
+            let n = node.lock().unwrap();
+            if n.max_dup == self.max_dup {
+                return n.mode_child();
             }
         }
-    }
-
-    /// Remove the node of `value` and propagate `height_information` to parent node.
-    /// 
-    /// If `value` is a duplicate, return and remove only one.
-    pub fn remove_child(&mut self, value: T) -> Result<DeltaDiff, ()> {
-        if value == self.value {
-            return Err(());
+        if self.n_ledu.1 == self.max_dup {
+            return (self.value, self.n_ledu.1 + 1);
         }
-        if value < self.value {
-            let (new_child, fin, reconnect) = match &self.left {
-                Some(node) => {
-                    let mut n = node.lock().unwrap();
-                    if n.value == value {
-                        if n.n_ledu.1 > 0 {
-                            n.n_ledu.1 -= 1;
-                            (None, Ok(Some(DeltaDiff::Zero)), false)
-                        } else {
-                            n.remove_reconnect()
-                        }
-                    } else {
-                        (None, Err(()), false)
-                    }
-                }
-                None => (None, Ok(None), false),
-            };
-            if reconnect {
-                self.left = new_child;
-            }
-            match fin {
-                Ok(Some(d_diff)) => {
-                    Ok(self.balance(d_diff, Direction::Left))
-                }
-                Ok(None) => {
-                    Err(())
-                }
-                Err(()) => {
-                    match self.left.clone().unwrap().lock().unwrap().remove_child(value) {
-                        Ok(d_diff) => {
-                            Ok(self.balance(d_diff, Direction::Left))
-                        }
-                        Err(()) => Err(())
-                    }
-                }
-            }
-        } else {
-            let (new_child, fin, reconnect) = match &self.right {
-                Some(node) => {
-                    let mut n = node.lock().unwrap();
-                    if n.value == value {
-                        if n.n_ledu.1 > 0 {
-                            n.n_ledu.1 -= 1;
-                            (None, Ok(Some(DeltaDiff::Zero)), false)
-                        } else {
-                            n.remove_reconnect()
-                        }
-                    } else {
-                        (None, Err(()), false)
-                    }
-                }
-                None => (None, Ok(None), false),
-            };
-            if reconnect {
-                self.right = new_child;
-            }
-            match fin {
-                Ok(Some(d_diff)) => {
-                    Ok(self.balance(d_diff, Direction::Right))
-                }
-                Ok(None) => {
-                    Err(())
-                }
-                Err(()) => {
-                    match self.right.clone().unwrap().lock().unwrap().remove_child(value) {
-                        Ok(d_diff) => {
-                            Ok(self.balance(d_diff, Direction::Right))
-                        }
-                        Err(()) => Err(())
-                    }
-                }
-            }
+        match &self.right {
+            Some(node) => node.lock().unwrap().mode_child(),
+            None => panic!("max_dup is inconsistent with the children"),
         }
     }
 
-    /// Utility function for removing a node.
-    fn remove_reconnect(&mut self) -> (Option<Arc<Mutex<Self>>>, Result<Option<DeltaDiff>, ()>, bool) {
-        match (&self.left, &self.right) {
+    /// Remove the value of this node with its duplicates and propagate `(data, height_information)` to parent node.
+    /// 
+    /// `None` means that this node has been removed, so the parent node must drop it.
+    pub fn remove_self(&mut self) -> (D, Option<DeltaDiff>) {
+        let data = std::mem::take(&mut self.data);
+        let d_diff = match (&self.left, &self.right) {
             (Some(nl), Some(nr)) => {
-                let n_m = if self.diff >= 0 {
-                    nl.lock().unwrap().max_child()
+                if self.diff >= 0 {
+                    let ((value, dup), data, d_diff) = nl.lock().unwrap().pop_max_all_child();
+                    self.value = value;
+                    self.data = data;
+                    self.n_ledu.0 -= dup + 1;
+                    self.n_ledu.1 = dup;
+                    Some(self.balance_child_removed(d_diff, Direction::Left))
                 } else {
-                    nr.lock().unwrap().min_child()
-                };
-                self.value = n_m;
-                self.n_ledu.1 = self.search(n_m).unwrap();
-                match self.remove_child(n_m) {
-                    Ok(d_diff) => (None, Ok(Some(d_diff)), false),
-                    Err(()) => panic!(),
+                    let ((value, dup), data, d_diff) = nr.lock().unwrap().pop_min_all_child();
+                    self.value = value;
+                    self.data = data;
+                    self.n_ledu.1 = dup;
+                    Some(self.balance_child_removed(d_diff, Direction::Right))
                 }
             }
-            (Some(nl), None) => {
-                (Some(nl.clone()), Ok(Some(self.balance(DeltaDiff::Shorter, Direction::Left))), true)
-            }
-            (None, Some(nr)) => {
-                (Some(nr.clone()), Ok(Some(self.balance(DeltaDiff::Shorter, Direction::Right))), true)
+            (Some(_), None) | (None, Some(_)) => {
+                let child = match self.left.take() {
+                    Some(node) => node,
+                    None => self.right.take().unwrap(),
+                };
+                let mut n = child.lock().unwrap();
+                self.value = n.value;
+                self.data = std::mem::take(&mut n.data);
+                self.diff = n.diff;
+                self.n_ledu = n.n_ledu;
+                self.max_dup = n.max_dup;
+                self.len = n.len;
+                self.n_nodes = n.n_nodes;
+                self.height = n.height;
+                self.left = n.left.take();
+                self.right = n.right.take();
+                Some(DeltaDiff::Shorter)
             }
-            (None, None) => {
-                (None, Ok(Some(DeltaDiff::Shorter)), true)
+            (None, None) => None,
+        };
+        (data, d_diff)
+    }
+
+    /// Drop the child if it has been removed, then balance the tree at the bottom.
+    fn balance_child_removed(&mut self, d_diff: Option<DeltaDiff>, from_dir: Direction) -> DeltaDiff {
+        match d_diff {
+            Some(d_diff) => self.balance(d_diff, from_dir),
+            None => {
+                match from_dir {
+                    Direction::Left => { self.left = None; }
+                    Direction::Right => { self.right = None; }
+                }
+                self.balance(DeltaDiff::Shorter, from_dir)
             }
         }
     }
 
     /// Balance the tree at the bottom and propagate `height_information` to parent node.
     pub fn balance(&mut self, d_diff: DeltaDiff, from_dir: Direction) -> DeltaDiff {
-        match d_diff {
+        let d_diff = match d_diff {
             DeltaDiff::Zero => DeltaDiff::Zero,
             DeltaDiff::Longer => {
                 match from_dir {
@@ -280,6 +245,7 @@ impl<T: TreeElem> AvlNode<T> {
                                     }
                                 }
                             };
+                            let _ = self.rotate();
                             d_diff_rotate
                         } else if self.diff <= 1 && self.diff >= -1 {
                             if self.diff <= 0 {
@@ -293,9 +259,112 @@ impl<T: TreeElem> AvlNode<T> {
                     }
                 }
             }
+        };
+        self.update();
+        d_diff
+    }
+
+    /// Return the number of elements of this node decided by `data`.
+    ///
+    /// Empty data decides nothing, like the default data of a node created by `with_count`.
+    fn data_count(&self) -> Option<usize> {
+        self.data.count().filter(|&count| count > 0)
+    }
+
+    /// Update `max_dup`, `len`, `n_nodes`, `height` and the summary in `data` from the children.
+    ///
+    /// The number of duplicates is also updated if `data` counts the elements.
+    pub fn update(&mut self) {
+        if let Some(count) = self.data_count() {
+            self.n_ledu.1 = count - 1;
+        }
+        let left = self.left.as_ref().map(|node| node.lock().unwrap());
+        let right = self.right.as_ref().map(|node| node.lock().unwrap());
+        let (mut max_dup, mut len, mut n_nodes, mut height) = (self.n_ledu.1, self.n_ledu.1 + 1, 1, 0);
+        for node in left.iter().chain(right.iter()) {
+            max_dup = max_dup.max(node.max_dup);
+            len += node.len;
+            n_nodes += node.n_nodes;
+            height = height.max(node.height);
+        }
+        self.max_dup = max_dup;
+        self.len = len;
+        self.n_nodes = n_nodes;
+        self.height = height + 1;
+        self.data.refresh(left.as_ref().map(|node| &node.data), right.as_ref().map(|node| &node.data));
+    }
+
+    /// Validate the tree at the bottom and propagate `(height, number_of_elements, number_of_nodes)` to parent node.
+    ///
+    /// `in_range(value, lo, hi)` determines if the value of a node is between the bounds given by the ancestors.
+    pub fn validate_with_child<F: Fn(&T, Option<T>, Option<T>) -> bool>(
+        &self,
+        lo: Option<T>,
+        hi: Option<T>,
+        in_range: &F,
+        path: &mut Vec<Direction>,
+        seen: &mut HashSet<*const Mutex<Self>>,
+    ) -> Result<(usize, usize, usize), InvariantReport> {
+        let report = |path: &Vec<Direction>, violation| Err(InvariantReport { path: path.clone(), violation });
+        if !in_range(&self.value, lo, hi) {
+            return report(path, Violation::Order);
+        }
+        if self.diff.abs() > 1 {
+            return report(path, Violation::Unbalanced { diff: self.diff });
+        }
+
+        let mut children = [(0, 0, 0, 0); 2];
+        for (i, &(child, dir)) in [(&self.left, Direction::Left), (&self.right, Direction::Right)].iter().enumerate() {
+            if let Some(node) = child {
+                path.push(dir);
+                // A shared node is reported before locking it, since it may be an ancestor.
+                if !seen.insert(Arc::as_ptr(node)) {
+                    return report(path, Violation::Aliased);
+                }
+                let n = node.lock().unwrap();
+                let (lo, hi) = match dir {
+                    Direction::Left => (lo, Some(self.value)),
+                    Direction::Right => (Some(self.value), hi),
+                };
+                let (height, len, n_nodes) = n.validate_with_child(lo, hi, in_range, path, seen)?;
+                children[i] = (height, len, n_nodes, n.max_dup);
+                path.pop();
+            }
+        }
+        let [(hl, nl, ml, dl), (hr, nr, mr, dr)] = children;
+
+        let actual = hl as i32 - hr as i32;
+        if self.diff != actual {
+            return report(path, Violation::Diff { diff: self.diff, actual });
+        }
+        if self.n_ledu.0 != nl {
+            return report(path, Violation::LessCount { n_less: self.n_ledu.0, actual: nl });
+        }
+        let actual = self.n_ledu.1.max(dl).max(dr);
+        if self.max_dup != actual {
+            return report(path, Violation::MaxDup { max_dup: self.max_dup, actual });
+        }
+        let (height, len, n_nodes) = (hl.max(hr) + 1, nl + self.n_ledu.1 + 1 + nr, ml + 1 + mr);
+        if (self.len, self.n_nodes, self.height) != (len, n_nodes, height) {
+            return report(path, Violation::Cached { cached: (self.len, self.n_nodes, self.height), actual: (len, n_nodes, height) });
+        }
+        Ok((height, len, n_nodes))
+    }
+
+    /// Validate the structure of the tree at the bottom after a rotation with the `paranoid` feature.
+    ///
+    /// The order of the values is not checked, since it needs `TreeElem`.
+    #[cfg(feature = "paranoid")]
+    fn check_rotated(&self) {
+        if let Err(report) = self.validate_with_child(None, None, &|_, _, _| true, &mut Vec::new(), &mut HashSet::new()) {
+            panic!("rotation broke the tree: {}", report);
         }
     }
 
+    #[cfg(not(feature = "paranoid"))]
+    #[inline(always)]
+    fn check_rotated(&self) {}
+
     /// Rotate the tree at the bottom to balance it.
     pub fn rotate(&mut self) -> bool {
         if self.diff <= 1 && self.diff >= -1 {
@@ -308,7 +377,8 @@ impl<T: TreeElem> AvlNode<T> {
             let (nl_val, nl_n_ledu1, n_diff, nll_op) = match &mut self.left {
                 None => panic!(),
                 Some(nl_arc) => {
-                    let mut nl = nl_arc.lock().unwrap();
+                    let mut nl_guard = nl_arc.lock().unwrap();
+                    let nl = &mut *nl_guard;
                     let nl_val = nl.value;
                     let nl_n_ledu1 = nl.n_ledu.1;
                     let nll_op = nl.left.clone();
@@ -333,6 +403,7 @@ impl<T: TreeElem> AvlNode<T> {
                                 let nlr_val = nlr.value;
                                 let nlr_n_ledu1 = nlr.n_ledu.1;
                                 nlr.value = nl_val;
+                                std::mem::swap(&mut nl.data, &mut nlr.data);
                                 nlr.n_ledu.1 = nl_n_ledu1;
                                 let nlrl_op = nlr.left.clone();
                                 let nlrr_op = nlr.right.clone();
@@ -342,6 +413,7 @@ impl<T: TreeElem> AvlNode<T> {
                                     None => 0,
                                 };
                                 nlr.right = nlrl_op;
+                                nlr.update();
                                 (nlr_val, nlr_n_ledu1, nlrr_op, diff)
                             }
                         };
@@ -355,6 +427,7 @@ impl<T: TreeElem> AvlNode<T> {
                             None => 0,
                         };
                         nl.diff = diff;
+                        nl.update();
                     }
                     let n_diff = if nl.diff == 2 {
                         nl.diff = -1;
@@ -374,11 +447,13 @@ impl<T: TreeElem> AvlNode<T> {
                     let nl_val = nl.value;
                     let nl_n_ledu1 = nl.n_ledu.1;
                     nl.value = n_val;
+                    std::mem::swap(&mut self.data, &mut nl.data);
                     nl.n_ledu.1 = n_n_ledu1;
                     nl.n_ledu.0 = match &nl.left {
                         Some(node) => node.lock().unwrap().len_child_and_self(),
                         None => 0,
                     };
+                    nl.update();
                     (nl_val, nl_n_ledu1, n_diff, nll_op)
                 }
             };
@@ -391,8 +466,10 @@ impl<T: TreeElem> AvlNode<T> {
                 Some(node) => node.lock().unwrap().len_child_and_self(),
                 None => 0,
             };
+            self.update();
+            self.check_rotated();
 
-            return true;
+            true
         } else if self.diff == -2 {
             let n_val = self.value;
             let n_n_ledu1 = self.n_ledu.1;
@@ -402,7 +479,8 @@ impl<T: TreeElem> AvlNode<T> {
                     panic!()
                 }
                 Some(nr_arc) => {
-                    let mut nr = nr_arc.lock().unwrap();
+                    let mut nr_guard = nr_arc.lock().unwrap();
+                    let nr = &mut *nr_guard;
                     let nr_val = nr.value;
                     let nr_n_ledu1 = nr.n_ledu.1;
                     let nrr_op = nr.right.clone();
@@ -427,6 +505,7 @@ impl<T: TreeElem> AvlNode<T> {
                                 let nrl_val = nrl.value;
                                 let nrl_n_ledu1 = nrl.n_ledu.1;
                                 nrl.value = nr_val;
+                                std::mem::swap(&mut nr.data, &mut nrl.data);
                                 nrl.n_ledu.1 = nr_n_ledu1;
                                 let nrlr_op = nrl.right.clone();
                                 let nrll_op = nrl.left.clone();
@@ -436,6 +515,7 @@ impl<T: TreeElem> AvlNode<T> {
                                     Some(node) => node.lock().unwrap().len_child_and_self(),
                                     None => 0,
                                 };
+                                nrl.update();
                                 (nrl_val, nrl_n_ledu1, nrll_op, diff)
                             }
                         };
@@ -449,6 +529,7 @@ impl<T: TreeElem> AvlNode<T> {
                         };
                         nr.right = nrl_op;
                         nr.diff = diff;
+                        nr.update();
                     }
                     let n_diff = if nr.diff == -2 {
                         nr.diff = 1;
@@ -468,11 +549,13 @@ impl<T: TreeElem> AvlNode<T> {
                     let nr_val = nr.value;
                     let nr_n_ledu1 = nr.n_ledu.1;
                     nr.value = n_val;
+                    std::mem::swap(&mut self.data, &mut nr.data);
                     nr.n_ledu.1 = n_n_ledu1;
                     nr.n_ledu.0 = match &nr.left {
                         Some(node) => node.lock().unwrap().len_child_and_self(),
                         None => 0,
                     };
+                    nr.update();
                     (nr_val, nr_n_ledu1, n_diff, nrr_op)
                 }
             };
@@ -485,8 +568,10 @@ impl<T: TreeElem> AvlNode<T> {
                 Some(node) => node.lock().unwrap().len_child_and_self(),
                 None => 0,
             };
+            self.update();
+            self.check_rotated();
 
-            return true;
+            true
         } else {
             panic!()
         }
@@ -500,181 +585,163 @@ impl<T: TreeElem> AvlNode<T> {
         }
     }
 
-    /// Return and remove the maximum value, 
+    /// Return and remove the maximum value in the tree at the bottom, 
     /// and propagate `(max_value, height_information)` to parent node.
     /// 
     /// If the maximum value is a duplicate, return and remove only one.
-    pub fn pop_max_child(&mut self) -> Result<(T, DeltaDiff), ()> {
+    /// `None` means that this node has been removed, so the parent node must drop it.
+    pub fn pop_max_child(&mut self) -> (T, Option<DeltaDiff>) {
         let res = match &self.right {
-            Some(node) => {
-                let mut n = node.lock().unwrap();
-                match &n.right {
-                    Some(_) => {
-                        match &n.pop_max_child() {
-                            Ok((value, d_diff)) => Ok((*value, *d_diff, None)),
-                            Err(()) => Err(()),
-                        }
-                    }
-                    None => {
-                        let value = n.value;
-                        if n.n_ledu.1 > 0 {
-                            n.n_ledu.1 -= 1;
-                            Ok((value, DeltaDiff::Zero, None))
-                        } else {
-                            Ok((value, DeltaDiff::Shorter, Some(n.left.clone())))
-                        }
-                    }
+            Some(node) => node.lock().unwrap().pop_max_child(),
+            None => {
+                if self.n_ledu.1 > 0 {
+                    self.n_ledu.1 -= 1;
+                    self.update();
+                    return (self.value, Some(DeltaDiff::Zero));
+                } else {
+                    return (self.value, self.remove_self().1);
                 }
             }
-            None => Err(()),
         };
-        match res {
-            Ok((value, d_diff, reconnect)) => {
-                match reconnect {
-                    Some(node_left) => { self.right = node_left; }
-                    None => {}
-                }
-                Ok((value, self.balance(d_diff, Direction::Right)))
-            }
-            Err(()) => Err(()),
-        }
+        let (value, d_diff) = res;
+        (value, Some(self.balance_child_removed(d_diff, Direction::Right)))
     }
 
-    /// Propagate `((max_value, number_of_duplicates), height_information)` to parent node, 
+    /// Propagate `((max_value, number_of_duplicates), data, height_information)` to parent node, 
     /// then remove its node.
-    pub fn pop_max_all_child(&mut self) -> Result<((T, usize), DeltaDiff), ()> {
+    /// 
+    /// `None` means that this node has been removed, so the parent node must drop it.
+    pub fn pop_max_all_child(&mut self) -> ((T, usize), D, Option<DeltaDiff>) {
         let res = match &self.right {
-            Some(node) => {
-                let mut n = node.lock().unwrap();
-                match &n.right {
-                    Some(_) => {
-                        match &n.pop_max_all_child() {
-                            Ok((value, d_diff)) => Ok((*value, *d_diff, None)),
-                            Err(()) => Err(()),
-                        }
-                    }
-                    None => {
-                        let value = n.value;
-                        Ok(((value, n.n_ledu.1), DeltaDiff::Zero, Some(n.left.clone())))
-                    }
-                }
+            Some(node) => node.lock().unwrap().pop_max_all_child(),
+            None => {
+                let value = (self.value, self.n_ledu.1);
+                let (data, d_diff) = self.remove_self();
+                return (value, data, d_diff);
             }
-            None => Err(()),
         };
-        match res {
-            Ok((value, d_diff, reconnect)) => {
-                match reconnect {
-                    Some(node_left) => { self.right = node_left; }
-                    None => {}
-                }
-                Ok((value, self.balance(d_diff, Direction::Right)))
-            }
-            Err(()) => Err(()),
-        }
+        let (value, data, d_diff) = res;
+        (value, data, Some(self.balance_child_removed(d_diff, Direction::Right)))
     }
 
-    /// Propagate the maximum value in the tree at the bottom to parent node.
+    /// Propagate the minimum value in the tree at the bottom to parent node.
     pub fn min_child(&self) -> T {
         match &self.left {
-            Some(node) => node.lock().unwrap().max_child(),
+            Some(node) => node.lock().unwrap().min_child(),
             None => self.value,
         }
     }
 
-    /// Return and remove the maximum value, 
-    /// and propagate `(max_value, height_information)` to parent node.
+    /// Return and remove the minimum value in the tree at the bottom, 
+    /// and propagate `(min_value, height_information)` to parent node.
     /// 
-    /// If the maximum value is a duplicate, return and remove only one.
-    pub fn pop_min_child(&mut self) -> Result<(T, DeltaDiff), ()> {
+    /// If the minimum value is a duplicate, return and remove only one.
+    /// `None` means that this node has been removed, so the parent node must drop it.
+    pub fn pop_min_child(&mut self) -> (T, Option<DeltaDiff>) {
         let res = match &self.left {
-            Some(node) => {
-                let mut n = node.lock().unwrap();
-                match &n.left {
-                    Some(_) => {
-                        match &n.pop_min_child() {
-                            Ok((value, d_diff)) => Ok((*value, *d_diff, None)),
-                            Err(()) => Err(()),
-                        }
-                    }
-                    None => {
-                        let value = n.value;
-                        if n.n_ledu.1 > 0 {
-                            n.n_ledu.1 -= 1;
-                            Ok((value, DeltaDiff::Zero, None))
-                        } else {
-                            Ok((value, DeltaDiff::Shorter, Some(n.right.clone())))
-                        }
-                    }
+            Some(node) => node.lock().unwrap().pop_min_child(),
+            None => {
+                if self.n_ledu.1 > 0 {
+                    self.n_ledu.1 -= 1;
+                    self.update();
+                    return (self.value, Some(DeltaDiff::Zero));
+                } else {
+                    return (self.value, self.remove_self().1);
                 }
             }
-            None => Err(()),
         };
-        match res {
-            Ok((value, d_diff, reconnect)) => {
-                match reconnect {
-                    Some(node_right) => { self.left = node_right; }
-                    None => {}
-                }
-                Ok((value, self.balance(d_diff, Direction::Left)))
-            }
-            Err(()) => Err(()),
-        }
+        let (value, d_diff) = res;
+        self.n_ledu.0 -= 1;
+        (value, Some(self.balance_child_removed(d_diff, Direction::Left)))
     }
 
-    /// Propagate `((max_value, number_of_duplicates), height_information)` to parent node, 
+    /// Propagate `((min_value, number_of_duplicates), data, height_information)` to parent node, 
     /// then remove its node.
-    pub fn pop_min_all_child(&mut self) -> Result<((T, usize), DeltaDiff), ()> {
+    /// 
+    /// `None` means that this node has been removed, so the parent node must drop it.
+    pub fn pop_min_all_child(&mut self) -> ((T, usize), D, Option<DeltaDiff>) {
         let res = match &self.left {
-            Some(node) => {
-                let mut n = node.lock().unwrap();
-                match &n.left {
-                    Some(_) => {
-                        match &n.pop_min_all_child() {
-                            Ok((value, d_diff)) => Ok((*value, *d_diff, None)),
-                            Err(()) => Err(()),
-                        }
-                    }
-                    None => {
-                        Ok(((n.value, n.n_ledu.1), DeltaDiff::Shorter, Some(n.right.clone())))
-                    }
-                }
+            Some(node) => node.lock().unwrap().pop_min_all_child(),
+            None => {
+                let value = (self.value, self.n_ledu.1);
+                let (data, d_diff) = self.remove_self();
+                return (value, data, d_diff);
             }
-            None => Err(()),
         };
-        match res {
-            Ok((value, d_diff, reconnect)) => {
-                match reconnect {
-                    Some(node_right) => { self.left = node_right; }
-                    None => {}
-                }
+        let ((value, dup), data, d_diff) = res;
+        self.n_ledu.0 -= dup + 1;
+        ((value, dup), data, Some(self.balance_child_removed(d_diff, Direction::Left)))
+    }
 
-                Ok((value, self.balance(d_diff, Direction::Left)))
-            }
-            Err(()) => Err(()),
+    /// Build a balanced tree from distinct values and the numbers of them in ascending order,
+    /// and return it with its height and the number of elements.
+    /// 
+    /// Every number must be positive.
+    pub fn build_child(counts: &[(T, usize)]) -> Option<(Self, usize, usize)> {
+        if counts.is_empty() {
+            return None;
+        }
+        let mid = counts.len() / 2;
+        let (value, count) = counts[mid];
+        let (left, h_left, n_left) = match Self::build_child(&counts[..mid]) {
+            Some((node, h, n)) => (Some(Arc::new(Mutex::new(node))), h, n),
+            None => (None, 0, 0),
+        };
+        let (right, h_right, n_right) = match Self::build_child(&counts[mid + 1..]) {
+            Some((node, h, n)) => (Some(Arc::new(Mutex::new(node))), h, n),
+            None => (None, 0, 0),
+        };
+        let mut node = Self::new(value);
+        node.diff = h_left as i32 - h_right as i32;
+        node.n_ledu = (n_left, count - 1);
+        node.left = left;
+        node.right = right;
+        node.update();
+        Some((node, 1 + h_left.max(h_right), n_left + count + n_right))
+    }
+
+    /// Push the values and the numbers of them in the tree at the bottom to `out` in ascending order.
+    pub fn collect_child(&self, out: &mut Vec<(T, usize)>) {
+        if let Some(node) = &self.left {
+            node.lock().unwrap().collect_child(out);
+        }
+        out.push((self.value, self.n_ledu.1 + 1));
+        if let Some(node) = &self.right {
+            node.lock().unwrap().collect_child(out);
+        }
+    }
+
+    /// Push `f(node)` of the nodes in the tree at the bottom to `out` in order.
+    pub fn collect_with_child<R, F: FnMut(&Self) -> R>(&self, out: &mut Vec<R>, f: &mut F) {
+        if let Some(node) = &self.left {
+            node.lock().unwrap().collect_with_child(out, f);
+        }
+        out.push(f(self));
+        if let Some(node) = &self.right {
+            node.lock().unwrap().collect_with_child(out, f);
+        }
+    }
+
+    /// Call `f` with the nodes in the tree at the bottom in order until it returns an error.
+    pub fn try_for_each_child<E, F: FnMut(&Self) -> Result<(), E>>(&self, f: &mut F) -> Result<(), E> {
+        if let Some(node) = &self.left {
+            node.lock().unwrap().try_for_each_child(f)?;
         }
+        f(self)?;
+        if let Some(node) = &self.right {
+            node.lock().unwrap().try_for_each_child(f)?;
+        }
+        Ok(())
     }
 
     /// Return the number of elements in the tree at the bottom including itself.
     pub fn len_child_and_self(&self) -> usize {
-        match &self.right {
-            Some(node) => self.n_ledu.0 + self.n_ledu.1 + 1 + node.lock().unwrap().len_child_and_self(),
-            None => self.n_ledu.0 + self.n_ledu.1 + 1,
-        }
+        self.len
     }
 
     /// Return the height of the tree at the bottom.
     pub fn height_child(&self) -> usize {
-        if self.diff >= 0 {
-            match &self.left {
-                Some(node) => 1 + node.lock().unwrap().height_child(),
-                None => 1,
-            }
-        } else {
-            match &self.right {
-                Some(node) => 1 + node.lock().unwrap().height_child(),
-                None => 1,
-            }
-        }
+        self.height
     }
 
     /// Utility function to test `diff`.
@@ -692,5 +759,618 @@ impl<T: TreeElem> AvlNode<T> {
             panic!("Diff value is incorrect!: diff={}, left={}, right={}", self.diff, hl, hr);
         }
     }
+
+    /// Propagate `f(node_of_kth_value, index_in_duplicates)` to parent node.
+    /// 
+    /// `f` may modify `data`, but must not modify the value and the structure of the node.
+    /// The summaries in `data` are refreshed on the way back to the root.
+    pub fn nth_mut_with_child<R, F: FnOnce(&mut Self, usize) -> R>(&mut self, k: usize, f: F) -> Option<R> {
+        let res = if k < self.n_ledu.0 {
+            match &self.left {
+                Some(node) => node.lock().unwrap().nth_mut_with_child(k, f),
+                None => None,
+            }
+        } else if k <= self.n_ledu.0 + self.n_ledu.1 {
+            let i = k - self.n_ledu.0;
+            Some(f(self, i))
+        } else {
+            match &self.right {
+                Some(node) => node.lock().unwrap().nth_mut_with_child(k - self.n_ledu.0 - self.n_ledu.1 - 1, f),
+                None => None,
+            }
+        };
+        self.update();
+        res
+    }
+
+    /// Insert a node holding `n` of `value` with `data` so that its elements start at position `k`,
+    /// and propagate `height_information` to parent node.
+    /// 
+    /// `k` must not be inside the duplicates of a node.
+    pub fn insert_nth_child(&mut self, k: usize, value: T, n: usize, data: D) -> DeltaDiff {
+        if k <= self.n_ledu.0 {
+            self.n_ledu.0 += n;
+            match &self.left {
+                Some(node) => {
+                    let d_diff = node.lock().unwrap().insert_nth_child(k, value, n, data);
+                    self.balance(d_diff, Direction::Left)
+                }
+                None => {
+                    self.left = Some(Arc::new(Mutex::new(Self::with_data(value, n, data))));
+                    self.balance(DeltaDiff::Longer, Direction::Left)
+                }
+            }
+        } else {
+            let k = k - self.n_ledu.0 - self.n_ledu.1 - 1;
+            match &self.right {
+                Some(node) => {
+                    let d_diff = node.lock().unwrap().insert_nth_child(k, value, n, data);
+                    self.balance(d_diff, Direction::Right)
+                }
+                None => {
+                    self.right = Some(Arc::new(Mutex::new(Self::with_data(value, n, data))));
+                    self.balance(DeltaDiff::Longer, Direction::Right)
+                }
+            }
+        }
+    }
+
+    /// Remove the node holding the `k`-th element with its duplicates,
+    /// and propagate `((value, number_of_duplicates), data, height_information)` to parent node.
+    /// 
+    /// `None` means that this node has been removed, so the parent node must drop it.
+    pub fn remove_nth_child(&mut self, k: usize) -> Option<((T, usize), D, Option<DeltaDiff>)> {
+        if k < self.n_ledu.0 {
+            let (value, data, d_diff) = match &self.left {
+                Some(node) => node.lock().unwrap().remove_nth_child(k)?,
+                None => return None,
+            };
+            self.n_ledu.0 -= value.1 + 1;
+            Some((value, data, Some(self.balance_child_removed(d_diff, Direction::Left))))
+        } else if k <= self.n_ledu.0 + self.n_ledu.1 {
+            let value = (self.value, self.n_ledu.1);
+            let (data, d_diff) = self.remove_self();
+            Some((value, data, d_diff))
+        } else {
+            let (value, data, d_diff) = match &self.right {
+                Some(node) => node.lock().unwrap().remove_nth_child(k - self.n_ledu.0 - self.n_ledu.1 - 1)?,
+                None => return None,
+            };
+            Some((value, data, Some(self.balance_child_removed(d_diff, Direction::Right))))
+        }
+    }
+
+    /// Join the tree `left`, the single node `mid` and the tree `right` in this order into a balanced tree.
+    pub fn join(left: Option<Self>, mut mid: Self, right: Option<Self>) -> Self {
+        let h_left = left.as_ref().map_or(0, |node| node.height_child());
+        let h_right = right.as_ref().map_or(0, |node| node.height_child());
+        if h_left > h_right + 1 {
+            let mut root = left.unwrap();
+            let _ = root.join_right_child(h_left, mid, right.map(|node| Arc::new(Mutex::new(node))), h_right);
+            root
+        } else if h_right > h_left + 1 {
+            let mut root = right.unwrap();
+            let n_added = left.as_ref().map_or(0, |node| node.len_child_and_self()) + mid.n_ledu.1 + 1;
+            let _ = root.join_left_child(h_right, left.map(|node| Arc::new(Mutex::new(node))), h_left, mid, n_added);
+            root
+        } else {
+            mid.n_ledu.0 = left.as_ref().map_or(0, |node| node.len_child_and_self());
+            mid.diff = h_left as i32 - h_right as i32;
+            mid.left = left.map(|node| Arc::new(Mutex::new(node)));
+            mid.right = right.map(|node| Arc::new(Mutex::new(node)));
+            mid.update();
+            mid
+        }
+    }
+
+    /// Attach `mid` with the tree `right` of height `h_right` at the right spine of this tree of height `h`,
+    /// and propagate `height_information` to parent node.
+    /// 
+    /// `h` must be larger than `h_right + 1`.
+    fn join_right_child(&mut self, h: usize, mut mid: Self, right: Option<Arc<Mutex<Self>>>, h_right: usize) -> DeltaDiff {
+        let h_child = if self.diff > 0 { h - 2 } else { h - 1 };
+        if h_child <= h_right + 1 {
+            let child = self.right.take();
+            mid.n_ledu.0 = match &child {
+                Some(node) => node.lock().unwrap().len_child_and_self(),
+                None => 0,
+            };
+            mid.diff = h_child as i32 - h_right as i32;
+            mid.left = child;
+            mid.right = right;
+            mid.update();
+            self.right = Some(Arc::new(Mutex::new(mid)));
+            self.balance(DeltaDiff::Longer, Direction::Right)
+        } else {
+            let d_diff = self.right.as_ref().unwrap().lock().unwrap().join_right_child(h_child, mid, right, h_right);
+            self.balance(d_diff, Direction::Right)
+        }
+    }
+
+    /// Attach the tree `left` of height `h_left` with `mid` at the left spine of this tree of height `h`,
+    /// and propagate `height_information` to parent node.
+    /// 
+    /// `h` must be larger than `h_left + 1`, and `n_added` is the number of elements in `left` and `mid`.
+    fn join_left_child(&mut self, h: usize, left: Option<Arc<Mutex<Self>>>, h_left: usize, mut mid: Self, n_added: usize) -> DeltaDiff {
+        let h_child = if self.diff < 0 { h - 2 } else { h - 1 };
+        self.n_ledu.0 += n_added;
+        if h_child <= h_left + 1 {
+            let child = self.left.take();
+            mid.n_ledu.0 = n_added - mid.n_ledu.1 - 1;
+            mid.diff = h_left as i32 - h_child as i32;
+            mid.left = left;
+            mid.right = child;
+            mid.update();
+            self.left = Some(Arc::new(Mutex::new(mid)));
+            self.balance(DeltaDiff::Longer, Direction::Left)
+        } else {
+            let d_diff = self.left.as_ref().unwrap().lock().unwrap().join_left_child(h_child, left, h_left, mid, n_added);
+            self.balance(d_diff, Direction::Left)
+        }
+    }
+
+    /// Split the tree at the bottom into the tree of the first `k` elements and the tree of the rest.
+    /// 
+    /// The duplicates of a node are not split; they go to the right if `k` is inside them.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a node of the tree is shared by another `Arc`, e.g. held by a `NodeRef`,
+    /// since the nodes are taken out of their `Arc`s to be rearranged.
+    pub fn split_nth(mut self, k: usize) -> (Option<Self>, Option<Self>) {
+        let left = self.left.take().map(Self::from_arc);
+        let right = self.right.take().map(Self::from_arc);
+        let n_left = self.n_ledu.0;
+        let n_self = self.n_ledu.1 + 1;
+        self.n_ledu.0 = 0;
+        self.diff = 0;
+        self.update();
+        if k < n_left {
+            let (ll, lr) = left.unwrap().split_nth(k);
+            (ll, Some(Self::join(lr, self, right)))
+        } else if k >= n_left + n_self {
+            let (rl, rr) = match right {
+                Some(node) => node.split_nth(k - n_left - n_self),
+                None => (None, None),
+            };
+            (Some(Self::join(left, self, rl)), rr)
+        } else {
+            (left, Some(Self::join(None, self, right)))
+        }
+    }
+
+    /// Take the node out of `Arc`.
+    /// 
+    /// # Panics
+    /// 
+    /// Panics if the node is shared.
+    fn from_arc(node: Arc<Mutex<Self>>) -> Self {
+        match Arc::try_unwrap(node) {
+            Ok(node) => node.into_inner().unwrap(),
+            Err(_) => panic!("the node is shared"),
+        }
+    }
 }
 
+impl<T: TreeElem, D: NodeData> AvlNode<T, D> {
+    /// Validate the tree at the bottom and propagate `(height, number_of_elements, number_of_nodes)` to parent node.
+    ///
+    /// The value must be between `lo` and `hi` given by the ancestors.
+    /// Equal values are allowed at the bounds, since `OrdEqElem` keeps them in separate nodes.
+    /// `path` is the path to this node, and `seen` holds the children already visited.
+    pub fn validate_child(
+        &self,
+        lo: Option<T>,
+        hi: Option<T>,
+        path: &mut Vec<Direction>,
+        seen: &mut HashSet<*const Mutex<Self>>,
+    ) -> Result<(usize, usize, usize), InvariantReport> {
+        let in_range = |value: &T, lo: Option<T>, hi: Option<T>| {
+            let below_lo = matches!(lo, Some(lo) if cmp_elem(*value, lo) == Ordering::Less);
+            let above_hi = matches!(hi, Some(hi) if cmp_elem(hi, *value) == Ordering::Less);
+            !below_lo && !above_hi
+        };
+        self.validate_with_child(lo, hi, &in_range, path, seen)
+    }
+
+    /// Push `value` and propagate `((number_of_less, number_of_duplicates), height_information)` to parent node.
+    pub fn push_child(&mut self, value: T) -> ((usize, usize), DeltaDiff) {
+        self.push_n_child(value, 1)
+    }
+
+    /// Push `n` of `value` and propagate `((number_of_less, number_of_duplicates), height_information)` to parent node.
+    /// 
+    /// `number_of_duplicates` is counted after pushing.
+    pub fn push_n_child(&mut self, value: T, n: usize) -> ((usize, usize), DeltaDiff) {
+        self.push_data_child(value, n, D::default(), |_, _| n)
+    }
+
+    /// Push `n` of `value` with `data` and propagate
+    /// `((number_of_less, number_of_duplicates), height_information)` to parent node.
+    /// 
+    /// If `value` exists, `merge` merges `data` into the data of its node
+    /// and returns the number of elements to add instead of `n`.
+    /// If the data counts the elements, `n` and the number returned by `merge` are ignored.
+    pub fn push_data_child<F>(&mut self, value: T, n: usize, data: D, merge: F) -> ((usize, usize), DeltaDiff)
+    where
+        F: FnOnce(&mut D, D) -> usize,
+    {
+        let (n_ledu, _, d_diff) = self.push_data_added_child(value, n, data, merge);
+        (n_ledu, d_diff)
+    }
+
+    /// `push_data_child` which also propagates the number of added elements.
+    fn push_data_added_child<F>(&mut self, value: T, n: usize, data: D, merge: F) -> ((usize, usize), usize, DeltaDiff)
+    where
+        F: FnOnce(&mut D, D) -> usize,
+    {
+        if value < self.value {
+            match &self.left {
+                Some(node) => {
+                    let (n_ledu, added, d_diff) = node.lock().unwrap().push_data_added_child(value, n, data, merge);
+                    self.n_ledu.0 += added;
+                    
+                    (n_ledu, added, self.balance(d_diff, Direction::Left))
+                }
+                _ => {
+                    let node = Self::with_data(value, n, data);
+                    let n = node.n_ledu.1 + 1;
+                    self.n_ledu.0 += n;
+                    self.left = Some(Arc::new(Mutex::new(node)));
+                    
+                    ((0, n - 1), n, self.balance(DeltaDiff::Longer, Direction::Left))
+                }
+            }
+        } else if value > self.value {
+            match &self.right {
+                Some(node) => {
+                    let (n_ledu, added, d_diff) = node.lock().unwrap().push_data_added_child(value, n, data, merge);
+                    
+                    ((n_ledu.0 + self.n_ledu.0 + self.n_ledu.1 + 1, n_ledu.1), added, self.balance(d_diff, Direction::Right))
+                }
+                _ => {
+                    let node = Self::with_data(value, n, data);
+                    let n = node.n_ledu.1 + 1;
+                    self.right = Some(Arc::new(Mutex::new(node)));
+                    
+                    ((self.n_ledu.0 + self.n_ledu.1 + 1, n - 1), n, self.balance(DeltaDiff::Longer, Direction::Right))
+                }
+            }
+        } else {
+            let dup = self.n_ledu.1;
+            self.n_ledu.1 += merge(&mut self.data, data);
+            self.update();
+            (self.n_ledu, self.n_ledu.1 - dup, DeltaDiff::Zero)
+        }
+    }
+
+    /// Search `value` and propagate `number_of_duplicates` to parent node.
+    pub fn search(&self, value: T) -> Option<usize> {
+        self.search_with_child(value, |node| node.n_ledu.1)
+    }
+
+    /// Search `value` and propagate `f(node_of_value)` to parent node.
+    pub fn search_with_child<R, F: FnOnce(&Self) -> R>(&self, value: T, f: F) -> Option<R> {
+        if value == self.value {
+            Some(f(self))
+        } else if value < self.value {
+            match &self.left {
+                Some(node) => node.lock().unwrap().search_with_child(value, f),
+                None => None,
+            }
+        } else {
+            match &self.right {
+                Some(node) => node.lock().unwrap().search_with_child(value, f),
+                None => None,
+            }
+        }
+    }
+
+    /// Search `value` and propagate `f(node_of_value)` to parent node.
+    /// 
+    /// `f` may modify `data`, but must not modify the value and the structure of the node.
+    /// The summaries in `data` are refreshed on the way back to the root.
+    pub fn search_mut_child<R, F: FnOnce(&mut Self) -> R>(&mut self, value: T, f: F) -> Option<R> {
+        let res = if value == self.value {
+            Some(f(self))
+        } else if value < self.value {
+            match &self.left {
+                Some(node) => node.lock().unwrap().search_mut_child(value, f),
+                None => None,
+            }
+        } else {
+            match &self.right {
+                Some(node) => node.lock().unwrap().search_mut_child(value, f),
+                None => None,
+            }
+        };
+        self.update();
+        res
+    }
+
+    /// Propagate `(number_of_less, number_of_duplicates)` of `value` to parent node.
+    /// 
+    /// `number_of_duplicates` is `None` if `value` does not exist.
+    pub fn rank_search_child(&self, value: T) -> (usize, Option<usize>) {
+        if value < self.value {
+            match &self.left {
+                Some(node) => node.lock().unwrap().rank_search_child(value),
+                None => (0, None),
+            }
+        } else if value > self.value {
+            let n_le = self.n_ledu.0 + self.n_ledu.1 + 1;
+            match &self.right {
+                Some(node) => {
+                    let (rank, dup) = node.lock().unwrap().rank_search_child(value);
+                    (n_le + rank, dup)
+                }
+                None => (n_le, None),
+            }
+        } else {
+            (self.n_ledu.0, Some(self.n_ledu.1))
+        }
+    }
+
+    /// Propagate the number of elements smaller than `value` to parent node.
+    pub fn rank_child(&self, value: T) -> usize {
+        if value < self.value {
+            match &self.left {
+                Some(node) => node.lock().unwrap().rank_child(value),
+                None => 0,
+            }
+        } else if value > self.value {
+            let n_le = self.n_ledu.0 + self.n_ledu.1 + 1;
+            match &self.right {
+                Some(node) => n_le + node.lock().unwrap().rank_child(value),
+                None => n_le,
+            }
+        } else {
+            self.n_ledu.0
+        }
+    }
+
+    /// Propagate the number of elements smaller than or equal to `value` to parent node.
+    pub fn rank_upper_child(&self, value: T) -> usize {
+        if value < self.value {
+            match &self.left {
+                Some(node) => node.lock().unwrap().rank_upper_child(value),
+                None => 0,
+            }
+        } else {
+            let n_le = self.n_ledu.0 + self.n_ledu.1 + 1;
+            if value > self.value {
+                match &self.right {
+                    Some(node) => n_le + node.lock().unwrap().rank_upper_child(value),
+                    None => n_le,
+                }
+            } else {
+                n_le
+            }
+        }
+    }
+
+    /// Remove `value` from the tree at the bottom and propagate `height_information` to parent node.
+    /// 
+    /// If `value` is a duplicate, remove only one.
+    /// `None` means that this node has been removed, so the parent node must drop it.
+    #[allow(clippy::result_unit_err)]
+    pub fn remove_child(&mut self, value: T) -> Result<Option<DeltaDiff>, ()> {
+        self.remove_n_child(value, 1).map(|(_, d_diff)| d_diff)
+    }
+
+    /// Remove `n` of `value` from the tree at the bottom
+    /// and propagate `(number_of_removed, height_information)` to parent node.
+    /// 
+    /// If there are `n` or fewer of `value`, remove all of them.
+    /// `None` means that this node has been removed, so the parent node must drop it.
+    #[allow(clippy::result_unit_err)]
+    pub fn remove_n_child(&mut self, value: T, n: usize) -> Result<(usize, Option<DeltaDiff>), ()> {
+        self.remove_n_data_child(value, n).map(|(_, removed, _, d_diff)| (removed, d_diff))
+    }
+
+    /// Remove `n` of `value` from the tree at the bottom
+    /// and propagate `(number_of_less, number_of_removed, removed_data, height_information)` to parent node.
+    /// 
+    /// `number_of_less` is the number of elements smaller than `value` in the tree at the bottom.
+    /// If there are `n` or fewer of `value`, remove all of them and its node,
+    /// and `removed_data` is the data of the node. Otherwise, `removed_data` is `None`.
+    /// If the data counts the elements, the node is always removed.
+    /// `None` as `height_information` means that this node has been removed, so the parent node must drop it.
+    #[allow(clippy::result_unit_err)]
+    pub fn remove_n_data_child(&mut self, value: T, n: usize) -> Result<(usize, usize, Option<D>, Option<DeltaDiff>), ()> {
+        let order = cmp_elem(value, self.value);
+        if order == Ordering::Less {
+            let (n_less, removed, data, d_diff) = match &self.left {
+                Some(node) => node.lock().unwrap().remove_n_data_child(value, n)?,
+                None => return Err(()),
+            };
+            self.n_ledu.0 -= removed;
+            Ok((n_less, removed, data, Some(self.balance_child_removed(d_diff, Direction::Left))))
+        } else if order == Ordering::Greater {
+            let n_le = self.n_ledu.0 + self.n_ledu.1 + 1;
+            let (n_less, removed, data, d_diff) = match &self.right {
+                Some(node) => node.lock().unwrap().remove_n_data_child(value, n)?,
+                None => return Err(()),
+            };
+            Ok((n_le + n_less, removed, data, Some(self.balance_child_removed(d_diff, Direction::Right))))
+        } else if n <= self.n_ledu.1 && self.data_count().is_none() {
+            self.n_ledu.1 -= n;
+            self.update();
+            Ok((self.n_ledu.0, n, None, Some(DeltaDiff::Zero)))
+        } else {
+            let (n_less, removed) = (self.n_ledu.0, self.n_ledu.1 + 1);
+            let (data, d_diff) = self.remove_self();
+            Ok((n_less, removed, Some(data), d_diff))
+        }
+    }
+
+    /// Remove the elements of `value` which `f` removes from the data of its node,
+    /// and propagate `(number_of_removed, height_information)` to parent node.
+    ///
+    /// `f` returns the number of removed elements, or `None` if it removes nothing.
+    /// If all the elements of `value` are removed, its node is removed as well.
+    /// `None` as `height_information` means that this node has been removed, so the parent node must drop it.
+    #[allow(clippy::result_unit_err)]
+    pub fn remove_with_data_child<F: FnOnce(&mut D) -> Option<usize>>(&mut self, value: T, f: F) -> Result<(usize, Option<DeltaDiff>), ()> {
+        let order = cmp_elem(value, self.value);
+        if order == Ordering::Less {
+            let (removed, d_diff) = match &self.left {
+                Some(node) => node.lock().unwrap().remove_with_data_child(value, f)?,
+                None => return Err(()),
+            };
+            self.n_ledu.0 -= removed;
+            Ok((removed, Some(self.balance_child_removed(d_diff, Direction::Left))))
+        } else if order == Ordering::Greater {
+            let (removed, d_diff) = match &self.right {
+                Some(node) => node.lock().unwrap().remove_with_data_child(value, f)?,
+                None => return Err(()),
+            };
+            Ok((removed, Some(self.balance_child_removed(d_diff, Direction::Right))))
+        } else {
+            let removed = f(&mut self.data).ok_or(())?;
+            if removed > self.n_ledu.1 {
+                Ok((removed, self.remove_self().1))
+            } else {
+                self.n_ledu.1 -= removed;
+                self.update();
+                Ok((removed, Some(DeltaDiff::Zero)))
+            }
+        }
+    }
+
+    /// Return the label of this node showing `value`, `diff` and `n_ledu`.
+    pub fn label(&self) -> String {
+        format!("{} (diff={}, less={}, dup={})", self.value, self.diff, self.n_ledu.0, self.n_ledu.1)
+    }
+
+    /// Write the nodes and edges of the tree at the bottom to `out` in Graphviz DOT, numbering the nodes from `id`.
+    ///
+    /// Return the next unused number.
+    pub fn dot_child(&self, id: usize, out: &mut String) -> usize {
+        let label = self.label().replace('\\', "\\\\").replace('"', "\\\"");
+        out.push_str(&format!("    n{} [label=\"{}\"];\n", id, label));
+        let mut next = id + 1;
+        for (child, side) in [(&self.left, "L"), (&self.right, "R")] {
+            if let Some(node) = child {
+                out.push_str(&format!("    n{} -> n{} [label=\"{}\"];\n", id, next, side));
+                next = node.lock().unwrap().dot_child(next, out);
+            }
+        }
+        next
+    }
+
+    /// Draw the tree at the bottom sideways to `out`, with the right child above and the left child below.
+    ///
+    /// `up`, `mid` and `down` are the prefixes of the lines above, at and below this node.
+    pub fn draw_child(&self, up: &str, mid: &str, down: &str, out: &mut String) {
+        if let Some(node) = &self.right {
+            node.lock().unwrap().draw_child(&format!("{}    ", up), &format!("{}┌── ", up), &format!("{}│   ", up), out);
+        }
+        out.push_str(mid);
+        out.push_str(&self.label());
+        out.push('\n');
+        if let Some(node) = &self.left {
+            node.lock().unwrap().draw_child(&format!("{}│   ", down), &format!("{}└── ", down), &format!("{}    ", down), out);
+        }
+    }
+}
+
+/// Node of AVL tree, which is the root borrowed from its owner or a child shared by `Arc`.
+pub(crate) enum NodeRef<'a, T, D = ()> {
+    Root(&'a AvlNode<T, D>),
+    Child(Arc<Mutex<AvlNode<T, D>>>),
+}
+
+/// Node locked through `NodeRef`.
+pub(crate) enum NodeGuard<'b, T, D = ()> {
+    Root(&'b AvlNode<T, D>),
+    Child(MutexGuard<'b, AvlNode<T, D>>),
+}
+
+impl<T, D> Deref for NodeGuard<'_, T, D> {
+    type Target = AvlNode<T, D>;
+
+    fn deref(&self) -> &Self::Target {
+        match self {
+            NodeGuard::Root(node) => node,
+            NodeGuard::Child(guard) => guard,
+        }
+    }
+}
+
+impl<'a, T, D> NodeRef<'a, T, D> {
+    /// Lock the node.
+    ///
+    /// A child node stays locked while the guard is alive.
+    pub fn lock(&self) -> NodeGuard<'_, T, D> {
+        match self {
+            NodeRef::Root(node) => NodeGuard::Root(node),
+            NodeRef::Child(node) => NodeGuard::Child(node.lock().unwrap()),
+        }
+    }
+}
+
+/// Iterator over the nodes whose values are in a range, in ascending order of the values.
+///
+/// Only the nodes on the path to the next node are kept, and each node is locked only while it is visited.
+pub(crate) struct RangeNodes<'a, T, D, R> {
+    range: R,
+    /// The nodes to visit, whose left subtrees have been visited or are out of the range.
+    stack: Vec<NodeRef<'a, T, D>>,
+}
+
+impl<'a, T: TreeElem, D, R: RangeBounds<T>> RangeNodes<'a, T, D, R> {
+    /// Create the iterator over the nodes in `range` of the tree under `root`.
+    pub fn new(root: Option<&'a AvlNode<T, D>>, range: R) -> Self {
+        let mut nodes = Self { range, stack: Vec::new() };
+        if let Some(r) = root {
+            nodes.push_left(NodeRef::Root(r));
+        }
+        nodes
+    }
+
+    /// Push `node` and the left descendants of it which are not before the start of the range.
+    fn push_left(&mut self, node: NodeRef<'a, T, D>) {
+        let mut next = Some(node);
+        while let Some(node) = next {
+            let (after_start, child) = {
+                let n = node.lock();
+                let after_start = match self.range.start_bound() {
+                    Bound::Included(start) => *start <= n.value,
+                    Bound::Excluded(start) => *start < n.value,
+                    Bound::Unbounded => true,
+                };
+                (after_start, if after_start { n.left.clone() } else { n.right.clone() })
+            };
+            if after_start {
+                self.stack.push(node);
+            }
+            next = child.map(NodeRef::Child);
+        }
+    }
+}
+
+impl<'a, T: TreeElem, D, R: RangeBounds<T>> Iterator for RangeNodes<'a, T, D, R> {
+    type Item = NodeRef<'a, T, D>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.stack.pop()?;
+        let (before_end, right) = {
+            let n = node.lock();
+            let before_end = match self.range.end_bound() {
+                Bound::Included(end) => n.value <= *end,
+                Bound::Excluded(end) => n.value < *end,
+                Bound::Unbounded => true,
+            };
+            (before_end, n.right.clone())
+        };
+        if !before_end {
+            self.stack.clear();
+            return None;
+        }
+        if let Some(right) = right {
+            self.push_left(NodeRef::Child(right));
+        }
+        Some(node)
+    }
+}
@@ -0,0 +1,108 @@
+//! Rank transform of data, like `scipy.stats.rankdata`.
+//!
+//! Ranks start from 1.
+//! NaN has no rank, and is ranked as NaN like `nan_policy="propagate"` of scipy.
+
+use crate::traits::{is_comparable, TreeElem};
+use crate::tree::AvlTree;
+
+/// How to assign ranks to tied elements.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RankMethod {
+    /// The average of the ranks that would have been assigned to all the tied elements.
+    Average,
+    /// The minimum of the ranks that would have been assigned to all the tied elements.
+    Min,
+    /// The maximum of the ranks that would have been assigned to all the tied elements.
+    Max,
+    /// Like `Min`, but the rank of the next larger element is the next integer.
+    Dense,
+    /// Distinct ranks in the order that the elements occur.
+    Ordinal,
+}
+
+impl RankMethod {
+    /// Rank of an element from
+    /// `(number_of_less, number_of_equal, index_in_equal, number_of_distinct_less)`.
+    fn rank(self, n_less: usize, n_equal: usize, nth: usize, n_distinct_less: usize) -> f64 {
+        match self {
+            RankMethod::Average => n_less as f64 + (n_equal + 1) as f64 / 2.0,
+            RankMethod::Min => (n_less + 1) as f64,
+            RankMethod::Max => (n_less + n_equal) as f64,
+            RankMethod::Dense => (n_distinct_less + 1) as f64,
+            RankMethod::Ordinal => (n_less + nth + 1) as f64,
+        }
+    }
+}
+
+/// Return the ranks of the elements of `data` over the whole `data`.
+///
+/// If `data` has NaN, every rank is NaN.
+pub fn rankdata<T: TreeElem>(data: &[T], method: RankMethod) -> Vec<f64> {
+    if !data.iter().all(is_comparable) {
+        return vec![f64::NAN; data.len()];
+    }
+    let mut tree = AvlTree::new();
+    let mut distinct = AvlTree::new();
+    let mut nths = Vec::with_capacity(data.len());
+    for &value in data.iter() {
+        let (_, dup) = tree.push(value);
+        if dup == 0 && method == RankMethod::Dense {
+            distinct.push(value);
+        }
+        nths.push(dup);
+    }
+
+    data.iter().zip(nths).map(|(&value, nth)| {
+        method.rank(tree.rank(value), tree.count(value), nth, distinct.rank(value))
+    }).collect()
+}
+
+/// Rank transform of a stream.
+///
+/// Each element is ranked over the elements pushed so far.
+pub struct RankStream<T> {
+    method: RankMethod,
+    tree: AvlTree<T>,
+    distinct: AvlTree<T>,
+}
+
+impl<T: TreeElem> RankStream<T> {
+    /// Create an empty stream.
+    pub fn new(method: RankMethod) -> Self {
+        Self { method, tree: AvlTree::new(), distinct: AvlTree::new() }
+    }
+
+    /// Push `value` and return the rank of it over the elements pushed so far, including itself.
+    ///
+    /// NaN is not pushed, and its rank is NaN.
+    pub fn push(&mut self, value: T) -> f64 {
+        if !is_comparable(&value) {
+            return f64::NAN;
+        }
+        let (n_less, dup) = self.tree.push(value);
+        if dup == 0 && self.method == RankMethod::Dense {
+            self.distinct.push(value);
+        }
+        self.method.rank(n_less, dup + 1, dup, self.distinct.rank(value))
+    }
+
+    /// Return the rank which `value` would have if it were pushed now.
+    pub fn rank(&self, value: T) -> f64 {
+        if !is_comparable(&value) {
+            return f64::NAN;
+        }
+        let dup = self.tree.count(value);
+        self.method.rank(self.tree.rank(value), dup + 1, dup, self.distinct.rank(value))
+    }
+
+    /// Return the number of elements pushed so far.
+    pub fn len(&self) -> usize {
+        self.tree.len()
+    }
+
+    /// Determine if no element has been pushed.
+    pub fn is_empty(&self) -> bool {
+        self.tree.is_empty()
+    }
+}
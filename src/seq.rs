@@ -0,0 +1,150 @@
+//! Sequence over AVL tree ordered by position, like a rope.
+//!
+//! The number of elements in the left subtree, which counts smaller values in `AvlTree`,
+//! is the position of a node here, so the same rotations keep the sequence balanced.
+
+use std::collections::HashSet;
+
+use crate::error::InvariantReport;
+use crate::node::AvlNode;
+
+/// Sequence of elements indexed by position over AVL tree.
+///
+/// Since the nodes are behind `Mutex`, elements are returned by cloning them,
+/// or accessed in closures by `get_with` and `get_mut_with`.
+pub struct AvlSeq<T> {
+    /// Root node. The data of every node is `Some`.
+    root: Option<AvlNode<(), Option<T>>>,
+}
+
+impl<T> Default for AvlSeq<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> AvlSeq<T> {
+    /// Create an empty sequence.
+    pub fn new() -> Self {
+        Self { root: None }
+    }
+
+    /// Insert `value` at `index`, shifting the elements after it to the right.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index > len`.
+    pub fn insert(&mut self, index: usize, value: T) {
+        let len = self.len();
+        assert!(index <= len, "insertion index (is {}) should be <= len (is {})", index, len);
+        match &mut self.root {
+            Some(r) => {
+                let _ = r.insert_nth_child(index, (), 1, Some(value));
+            }
+            None => self.root = Some(AvlNode::with_data((), 1, Some(value))),
+        }
+    }
+
+    /// Append `value` to the back of the sequence.
+    pub fn push(&mut self, value: T) {
+        self.insert(self.len(), value);
+    }
+
+    /// Remove and return the element at `index`, shifting the elements after it to the left.
+    pub fn remove(&mut self, index: usize) -> Option<T> {
+        let r = self.root.as_mut()?;
+        let (_, data, d_diff) = r.remove_nth_child(index)?;
+        if d_diff.is_none() {
+            self.root = None;
+        }
+        data
+    }
+
+    /// Return `f(element_at_index)`.
+    pub fn get_with<R, F: FnOnce(&T) -> R>(&self, index: usize, f: F) -> Option<R> {
+        match &self.root {
+            Some(r) => r.nth_with_child(index, |node, _| f(node.data.as_ref().unwrap())),
+            None => None,
+        }
+    }
+
+    /// Return `f(element_at_index)`, where `f` may modify the element.
+    pub fn get_mut_with<R, F: FnOnce(&mut T) -> R>(&mut self, index: usize, f: F) -> Option<R> {
+        match &mut self.root {
+            Some(r) => r.nth_mut_with_child(index, |node, _| f(node.data.as_mut().unwrap())),
+            None => None,
+        }
+    }
+
+    /// Split the sequence into the first `index` elements and the rest.
+    ///
+    /// If `index > len`, the first one has all the elements.
+    /// This does not panic, since the nodes of the sequence are never shared outside it.
+    pub fn split_at(self, index: usize) -> (Self, Self) {
+        match self.root {
+            Some(r) => {
+                let (left, right) = r.split_nth(index);
+                (Self { root: left }, Self { root: right })
+            }
+            None => (Self::new(), Self::new()),
+        }
+    }
+
+    /// Concatenate `self` and `other` in this order.
+    pub fn concat(self, other: Self) -> Self {
+        let mut right = match other.root {
+            Some(r) => r,
+            None => return self,
+        };
+        let (_, data, d_diff) = right.pop_min_all_child();
+        let mid = AvlNode::with_data((), 1, data);
+        Self { root: Some(AvlNode::join(self.root, mid, d_diff.map(|_| right))) }
+    }
+
+    /// Return the number of elements.
+    pub fn len(&self) -> usize {
+        match &self.root {
+            Some(r) => r.len_child_and_self(),
+            None => 0,
+        }
+    }
+
+    /// Determine if the sequence is empty.
+    pub fn is_empty(&self) -> bool {
+        self.root.is_none()
+    }
+
+    /// Return the height of the tree.
+    pub fn height(&self) -> usize {
+        match &self.root {
+            Some(r) => r.height_child(),
+            None => 0,
+        }
+    }
+
+    /// Validate the balance and the cached counts of every node.
+    ///
+    /// There is no order of values to check, since the nodes are ordered by position.
+    pub fn validate(&self) -> Result<(), InvariantReport> {
+        match &self.root {
+            Some(r) => r.validate_with_child(None, None, &|_, _, _| true, &mut Vec::new(), &mut HashSet::new()).map(|_| ()),
+            None => Ok(()),
+        }
+    }
+}
+
+impl<T: Clone> AvlSeq<T> {
+    /// Return the element at `index`.
+    pub fn get(&self, index: usize) -> Option<T> {
+        self.get_with(index, |value| value.clone())
+    }
+
+    /// Return the elements in order.
+    pub fn to_vec(&self) -> Vec<T> {
+        let mut v = Vec::with_capacity(self.len());
+        if let Some(r) = &self.root {
+            r.collect_with_child(&mut v, &mut |node| node.data.clone().unwrap());
+        }
+        v
+    }
+}
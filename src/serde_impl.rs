@@ -0,0 +1,158 @@
+//! Implementations of `Serialize` and `Deserialize` with the `serde` feature.
+//!
+//! `AvlTree` is serialized as a sequence of pairs of a distinct value and the number of it in ascending order,
+//! and the maps are serialized as sequences of pairs of a key and the values at it in ascending order of keys.
+//! The structure of the nodes and the capacity limit of the tree are not serialized.
+
+use std::fmt;
+use std::marker::PhantomData;
+
+use serde::de::{self, Deserialize, Deserializer, SeqAccess, Visitor};
+use serde::ser::{Serialize, SerializeSeq, Serializer};
+
+use crate::binary::check_next;
+use crate::map::{AvlMap, AvlMultiMap};
+use crate::traits::{OrdEqElem, TreeElem};
+use crate::tree::AvlTree;
+
+impl<T: TreeElem + Serialize> Serialize for OrdEqElem<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.value.serialize(serializer)
+    }
+}
+
+impl<'de, T: TreeElem + Deserialize<'de>> Deserialize<'de> for OrdEqElem<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        T::deserialize(deserializer).map(Self::new)
+    }
+}
+
+impl<T: TreeElem + Serialize> Serialize for AvlTree<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.merged_counts())
+    }
+}
+
+/// Visitor reading the pairs of `AvlTree`.
+struct TreeVisitor<T>(PhantomData<T>);
+
+impl<'de, T: TreeElem + Deserialize<'de>> Visitor<'de> for TreeVisitor<T> {
+    type Value = AvlTree<T>;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "a sequence of distinct values and the numbers of them in ascending order")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        // The size hint is not trusted to allocate the memory.
+        let mut counts: Vec<(T, usize)> = Vec::with_capacity(seq.size_hint().unwrap_or(0).min(1 << 16));
+        let mut len: usize = 0;
+        while let Some((value, count)) = seq.next_element::<(T, usize)>()? {
+            check_next(counts.last().map(|&(prev, _)| prev), value, count, counts.len() as u64)
+                .map_err(de::Error::custom)?;
+            len = len.checked_add(count).ok_or_else(|| de::Error::custom("too many elements"))?;
+            counts.push((value, count));
+        }
+        Ok(AvlTree::from_sorted_counts(&counts))
+    }
+}
+
+impl<'de, T: TreeElem + Deserialize<'de>> Deserialize<'de> for AvlTree<T> {
+    /// Deserialize the tree in `O(n)`.
+    ///
+    /// The values must be strictly ascending, and the numbers of them must not be zero.
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_seq(TreeVisitor(PhantomData))
+    }
+}
+
+impl<K: TreeElem + Serialize, V: Serialize> Serialize for AvlMap<K, V> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut seq = serializer.serialize_seq(Some(self.len()))?;
+        if let Some(r) = &self.root {
+            r.try_for_each_child(&mut |node| match &node.data {
+                Some(value) => seq.serialize_element(&(node.value, value)),
+                None => Ok(()),
+            })?;
+        }
+        seq.end()
+    }
+}
+
+/// Visitor reading the pairs of `AvlMap`.
+struct MapVisitor<K, V>(PhantomData<(K, V)>);
+
+impl<'de, K: TreeElem + Deserialize<'de>, V: Deserialize<'de>> Visitor<'de> for MapVisitor<K, V> {
+    type Value = AvlMap<K, V>;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "a sequence of keys and values")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let mut map = AvlMap::new();
+        let mut prev = None;
+        let mut i = 0;
+        while let Some((key, value)) = seq.next_element::<(K, V)>()? {
+            check_next(prev, key, 1, i).map_err(de::Error::custom)?;
+            map.insert(key, value);
+            prev = Some(key);
+            i += 1;
+        }
+        Ok(map)
+    }
+}
+
+impl<'de, K: TreeElem + Deserialize<'de>, V: Deserialize<'de>> Deserialize<'de> for AvlMap<K, V> {
+    /// Deserialize the map by inserting the pairs in order.
+    ///
+    /// The keys must be strictly ascending.
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_seq(MapVisitor(PhantomData))
+    }
+}
+
+impl<K: TreeElem + Serialize, V: Serialize> Serialize for AvlMultiMap<K, V> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut seq = serializer.serialize_seq(Some(self.distinct_len()))?;
+        if let Some(r) = &self.root {
+            r.try_for_each_child(&mut |node| seq.serialize_element(&(node.value, &node.data)))?;
+        }
+        seq.end()
+    }
+}
+
+/// Visitor reading the pairs of `AvlMultiMap`.
+struct MultiMapVisitor<K, V>(PhantomData<(K, V)>);
+
+impl<'de, K: TreeElem + Deserialize<'de>, V: Deserialize<'de>> Visitor<'de> for MultiMapVisitor<K, V> {
+    type Value = AvlMultiMap<K, V>;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "a sequence of keys and sequences of values")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let mut map = AvlMultiMap::new();
+        let mut prev = None;
+        let mut i = 0;
+        while let Some((key, values)) = seq.next_element::<(K, Vec<V>)>()? {
+            check_next(prev, key, values.len(), i).map_err(de::Error::custom)?;
+            for value in values {
+                map.insert(key, value);
+            }
+            prev = Some(key);
+            i += 1;
+        }
+        Ok(map)
+    }
+}
+
+impl<'de, K: TreeElem + Deserialize<'de>, V: Deserialize<'de>> Deserialize<'de> for AvlMultiMap<K, V> {
+    /// Deserialize the map by inserting the values in order.
+    ///
+    /// The keys must be strictly ascending, and the values at a key must not be empty.
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_seq(MultiMapVisitor(PhantomData))
+    }
+}
@@ -0,0 +1,83 @@
+//! Sorting functions using AVL tree.
+//!
+//! The tree keeps the duplicates of a value in a single node,
+//! so the cost is `O(n log d)` where `d` is the number of distinct values.
+//! Run `cargo bench` to compare them with `slice::sort` and `slice::sort_unstable`.
+
+use std::cmp::Ordering;
+
+use crate::traits::{cmp_elem, is_comparable, TreeElem};
+use crate::tree::AvlTree;
+
+/// Build the tree of the elements of `v`, which must not be NaN.
+fn build<T: TreeElem>(v: &[T]) -> AvlTree<T> {
+    assert!(v.iter().all(is_comparable), "NaN cannot be sorted");
+    let mut tree = AvlTree::new();
+    for &value in v.iter() {
+        tree.push(value);
+    }
+    tree
+}
+
+/// Sort `v` in ascending order.
+///
+/// # Panics
+///
+/// Panics if `v` has NaN.
+pub fn sort<T: TreeElem>(v: &mut [T]) {
+    let tree = build(v);
+    let mut i = 0;
+    for (value, count) in tree.to_vec_counts() {
+        for elem in v[i..i + count].iter_mut() {
+            *elem = value;
+        }
+        i += count;
+    }
+}
+
+/// Sort `v` in ascending order of the keys extracted by `f`.
+///
+/// This sort is stable.
+///
+/// # Panics
+///
+/// Panics if a key is NaN.
+pub fn sort_by_key<T: Clone, K: TreeElem, F: FnMut(&T) -> K>(v: &mut [T], f: F) {
+    let keys: Vec<K> = v.iter().map(f).collect();
+    let sorted: Vec<T> = argsort(&keys).into_iter().map(|i| v[i].clone()).collect();
+    v.clone_from_slice(&sorted);
+}
+
+/// Return the indices that sort `v` in ascending order.
+///
+/// The indices of equal elements keep their original order.
+///
+/// # Panics
+///
+/// Panics if `v` has NaN.
+pub fn argsort<T: TreeElem>(v: &[T]) -> Vec<usize> {
+    let tree = build(v);
+    // The distinct values and the next position of each of them in the sorted order.
+    let mut starts: Vec<(T, usize)> = Vec::with_capacity(tree.distinct_len());
+    let mut start = 0;
+    for (value, count) in tree.merged_counts() {
+        starts.push((value, start));
+        start += count;
+    }
+    let mut indices = vec![0; v.len()];
+    for (i, &value) in v.iter().enumerate() {
+        let j = starts.partition_point(|&(x, _)| cmp_elem(x, value) == Ordering::Less);
+        indices[starts[j].1] = i;
+        starts[j].1 += 1;
+    }
+    indices
+}
+
+/// Return the distinct values in `v` and the numbers of them in ascending order.
+///
+/// # Panics
+///
+/// Panics if `v` has NaN.
+pub fn sort_dedup_counts<T: TreeElem>(v: &[T]) -> Vec<(T, usize)> {
+    build(v).merged_counts()
+}
@@ -0,0 +1,353 @@
+//! Statistics using AVL tree.
+
+use num_traits::ToPrimitive;
+
+use std::cmp::Ordering;
+
+use crate::sort::argsort;
+use crate::traits::{cmp_elem, is_comparable, TreeElem};
+use crate::tree::AvlTree;
+
+/// Return the number of pairs `i < j` with `v[i] > v[j]`.
+///
+/// # Panics
+///
+/// Panics if `v` has NaN.
+pub fn count_inversions<T: TreeElem>(v: &[T]) -> u64 {
+    assert!(v.iter().all(is_comparable), "NaN cannot be counted");
+    let mut tree = AvlTree::new();
+    let mut n_inv = 0;
+    for (i, &value) in v.iter().enumerate() {
+        let (rank, dup) = tree.push(value);
+        n_inv += (i - rank - dup) as u64;
+    }
+    n_inv
+}
+
+/// The numbers of pairs needed for Kendall tau.
+struct KendallPairs {
+    /// All pairs.
+    n_all: u64,
+    /// Pairs tied in `x`.
+    n_tie_x: u64,
+    /// Pairs tied in `y`.
+    n_tie_y: u64,
+    /// Pairs tied in both `x` and `y`.
+    n_tie_xy: u64,
+    /// Discordant pairs.
+    n_discordant: u64,
+}
+
+impl KendallPairs {
+    fn new<T: TreeElem, U: TreeElem>(x: &[T], y: &[U]) -> Self {
+        assert_eq!(x.len(), y.len(), "x and y must have the same length");
+        // Sort the pairs by `x`, then by `y`.
+        let by_y = argsort(y);
+        let x_by_y: Vec<T> = by_y.iter().map(|&i| x[i]).collect();
+        let order: Vec<usize> = argsort(&x_by_y).into_iter().map(|i| by_y[i]).collect();
+
+        let mut tree = AvlTree::new();
+        let mut n_discordant = 0;
+        let mut n_tie_x = 0;
+        let mut n_tie_xy = 0;
+        let (mut run_x, mut run_xy) = (0, 0);
+        for (k, &i) in order.iter().enumerate() {
+            if k > 0 && x[i] == x[order[k - 1]] {
+                run_x += 1;
+                if y[i] == y[order[k - 1]] {
+                    run_xy += 1;
+                } else {
+                    run_xy = 0;
+                }
+            } else {
+                run_x = 0;
+                run_xy = 0;
+            }
+            n_tie_x += run_x;
+            n_tie_xy += run_xy;
+
+            let (rank, dup) = tree.push(y[i]);
+            n_discordant += (k - rank - dup) as u64;
+        }
+        let n_tie_y = tree.to_vec_counts().into_iter().map(|(_, count)| pairs(count)).sum();
+
+        Self { n_all: pairs(x.len()), n_tie_x, n_tie_y, n_tie_xy, n_discordant }
+    }
+
+    /// Concordant pairs minus discordant pairs.
+    fn n_score(&self) -> f64 {
+        self.n_all as f64 - self.n_tie_x as f64 - self.n_tie_y as f64 + self.n_tie_xy as f64
+            - 2.0 * self.n_discordant as f64
+    }
+}
+
+/// Return the number of pairs in `n` elements.
+fn pairs(n: usize) -> u64 {
+    let n = n as u64;
+    n * n.saturating_sub(1) / 2
+}
+
+/// Return Kendall tau-a between the paired sequences `x` and `y`.
+///
+/// Tied pairs are counted as neither concordant nor discordant.
+/// Return `None` if there are less than two pairs.
+///
+/// # Panics
+///
+/// Panics if `x` and `y` have different lengths or have NaN.
+pub fn kendall_tau_a<T: TreeElem, U: TreeElem>(x: &[T], y: &[U]) -> Option<f64> {
+    let kp = KendallPairs::new(x, y);
+    if kp.n_all == 0 {
+        return None;
+    }
+    Some(kp.n_score() / kp.n_all as f64)
+}
+
+/// Return Kendall tau-b between the paired sequences `x` and `y`.
+///
+/// This is corrected for ties in `x` and in `y`.
+/// Return `None` if all the elements of `x` or `y` are tied.
+///
+/// # Panics
+///
+/// Panics if `x` and `y` have different lengths or have NaN.
+pub fn kendall_tau_b<T: TreeElem, U: TreeElem>(x: &[T], y: &[U]) -> Option<f64> {
+    let kp = KendallPairs::new(x, y);
+    let denom = ((kp.n_all - kp.n_tie_x) as f64 * (kp.n_all - kp.n_tie_y) as f64).sqrt();
+    if denom == 0.0 {
+        return None;
+    }
+    Some(kp.n_score() / denom)
+}
+
+/// Return Kendall tau distance between two rankings `a` and `b` of the same items,
+/// i.e. the number of pairs of items ordered oppositely.
+///
+/// `a[i]` and `b[i]` are the ranks (or scores) of the item `i`.
+/// Pairs tied in either ranking are not counted.
+///
+/// # Panics
+///
+/// Panics if `a` and `b` have different lengths or have NaN.
+pub fn kendall_distance<T: TreeElem, U: TreeElem>(a: &[T], b: &[U]) -> u64 {
+    KendallPairs::new(a, b).n_discordant
+}
+
+/// Result of a rank test.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RankTest {
+    /// The test statistic.
+    pub statistic: f64,
+    /// The standard score by the normal approximation.
+    pub z: f64,
+    /// The two-sided p-value by the normal approximation.
+    pub p_value: f64,
+}
+
+impl RankTest {
+    fn new(statistic: f64, z: f64) -> Self {
+        let p_value = erfc(z.abs() / std::f64::consts::SQRT_2).min(1.0);
+        Self { statistic, z, p_value }
+    }
+}
+
+/// Return the complementary error function.
+///
+/// The fractional error is less than 1.2e-7 (Numerical Recipes, `erfcc`).
+fn erfc(x: f64) -> f64 {
+    let z = x.abs();
+    let t = 1.0 / (1.0 + 0.5 * z);
+    let r = t * (-z * z - 1.265_512_23 + t * (1.000_023_68 + t * (0.374_091_96 + t * (0.096_784_18
+        + t * (-0.186_288_06 + t * (0.278_868_07 + t * (-1.135_203_98 + t * (1.488_515_87
+        + t * (-0.822_152_23 + t * 0.170_872_77))))))))).exp();
+    if x >= 0.0 { r } else { 2.0 - r }
+}
+
+/// Return `t^3 - t` for a group of `t` tied elements.
+fn tie_term(t: usize) -> f64 {
+    let t = t as f64;
+    t * t * t - t
+}
+
+/// Call `f` with the numbers of each distinct value in `a` and in `b`, in ascending order of the values.
+///
+/// The trees are walked in order with cursors, so the values are not copied.
+///
+/// # Panics
+///
+/// Panics if the trees hold NaN.
+fn merge_counts<T: TreeElem, F: FnMut(usize, usize)>(a: &AvlTree<T>, b: &AvlTree<T>, mut f: F) {
+    let (mut cursor_a, mut cursor_b) = (a.cursor_first(), b.cursor_first());
+    loop {
+        let (current_a, current_b) = (cursor_a.current(), cursor_b.current());
+        assert!(current_a.iter().chain(current_b.iter()).all(|(x, _, _)| is_comparable(x)), "NaN cannot be ranked");
+        let (c_a, c_b) = match (current_a, current_b) {
+            (None, None) => return,
+            (Some((_, _, dup)), None) => (dup + 1, 0),
+            (None, Some((_, _, dup))) => (0, dup + 1),
+            (Some((x, _, dup_x)), Some((y, _, dup_y))) => match cmp_elem(x, y) {
+                Ordering::Equal => (dup_x + 1, dup_y + 1),
+                Ordering::Less => (dup_x + 1, 0),
+                Ordering::Greater => (0, dup_y + 1),
+            },
+        };
+        // A cursor at the ghost position would move to the minimum again, so only the taken ones move.
+        if c_a > 0 {
+            cursor_a.move_next();
+        }
+        if c_b > 0 {
+            cursor_b.move_next();
+        }
+        f(c_a, c_b);
+    }
+}
+
+/// Mann-Whitney U test between the samples `x` and `y`.
+///
+/// See [`mann_whitney_u_trees`].
+///
+/// # Panics
+///
+/// Panics if `x` or `y` has NaN.
+pub fn mann_whitney_u<T: TreeElem>(x: &[T], y: &[T]) -> Option<RankTest> {
+    let mut tree_x = AvlTree::new();
+    for &value in x.iter() {
+        tree_x.push(value);
+    }
+    let mut tree_y = AvlTree::new();
+    for &value in y.iter() {
+        tree_y.push(value);
+    }
+    mann_whitney_u_trees(&tree_x, &tree_y)
+}
+
+/// Mann-Whitney U test between the samples held in the trees `x` and `y`.
+///
+/// The statistic is U of `x`.
+/// The variance is corrected for ties and the standard score uses the continuity correction.
+/// Return `None` if either sample is empty or all the elements are tied.
+///
+/// # Panics
+///
+/// Panics if `x` or `y` holds NaN.
+pub fn mann_whitney_u_trees<T: TreeElem>(x: &AvlTree<T>, y: &AvlTree<T>) -> Option<RankTest> {
+    let (n_x, n_y) = (x.len(), y.len());
+    if n_x == 0 || n_y == 0 {
+        return None;
+    }
+
+    let mut n_less = 0;
+    let mut rank_sum_x = 0.0;
+    let mut ties = 0.0;
+    merge_counts(x, y, |c_x, c_y| {
+        let t = c_x + c_y;
+        rank_sum_x += c_x as f64 * (n_less as f64 + (t + 1) as f64 / 2.0);
+        ties += tie_term(t);
+        n_less += t;
+    });
+
+    let (n_x, n_y) = (n_x as f64, n_y as f64);
+    let n = n_x + n_y;
+    let u = rank_sum_x - n_x * (n_x + 1.0) / 2.0;
+    let mean = n_x * n_y / 2.0;
+    let sd = (n_x * n_y / 12.0 * ((n + 1.0) - ties / (n * (n - 1.0)))).sqrt();
+    if sd.is_nan() || sd <= 0.0 {
+        return None;
+    }
+    let z = ((u - mean).abs() - 0.5).max(0.0) / sd;
+    Some(RankTest::new(u, if u < mean { -z } else { z }))
+}
+
+/// Wilcoxon signed-rank test of the paired samples `x` and `y`.
+///
+/// The differences `x[i] - y[i]` equal to zero are dropped.
+/// The statistic is the smaller of the rank sums of the positive and the negative differences,
+/// and the standard score is that of the rank sum of the positive differences.
+/// The variance is corrected for ties.
+/// Return `None` if all the differences are zero, which includes `x` and `y` being empty.
+///
+/// # Panics
+///
+/// Panics if `x` and `y` have different lengths, an element cannot be converted to `f64`,
+/// or a difference is NaN.
+pub fn wilcoxon<T: TreeElem + ToPrimitive>(x: &[T], y: &[T]) -> Option<RankTest> {
+    assert_eq!(x.len(), y.len(), "x and y must have the same length");
+    let to_f64 = |value: &T| value.to_f64().expect("the elements must be convertible to f64");
+    let mut diffs = Vec::with_capacity(x.len());
+    for (a, b) in x.iter().zip(y.iter()) {
+        let d = to_f64(a) - to_f64(b);
+        assert!(!d.is_nan(), "NaN cannot be ranked");
+        if d != 0.0 {
+            diffs.push(d);
+        }
+    }
+    if diffs.is_empty() {
+        return None;
+    }
+
+    let mut tree = AvlTree::new();
+    for d in diffs.iter() {
+        tree.push(d.abs());
+    }
+    let mut rank_sum_pos = 0.0;
+    for &d in diffs.iter().filter(|&&d| d > 0.0) {
+        rank_sum_pos += tree.rank(d) as f64 + (tree.count(d) + 1) as f64 / 2.0;
+    }
+    let ties: f64 = tree.to_vec_counts().into_iter().map(|(_, count)| tie_term(count)).sum();
+
+    let n = diffs.len() as f64;
+    let rank_sum_neg = n * (n + 1.0) / 2.0 - rank_sum_pos;
+    let mean = n * (n + 1.0) / 4.0;
+    let sd = (n * (n + 1.0) * (2.0 * n + 1.0) / 24.0 - ties / 48.0).sqrt();
+    if sd.is_nan() || sd <= 0.0 {
+        return None;
+    }
+    Some(RankTest::new(rank_sum_pos.min(rank_sum_neg), (rank_sum_pos - mean) / sd))
+}
+
+/// Return the two-sample Kolmogorov-Smirnov statistic between the samples held in the trees `a` and `b`,
+/// i.e. the maximum distance between their empirical distribution functions.
+///
+/// Return `None` if either sample is empty.
+///
+/// # Panics
+///
+/// Panics if `a` or `b` holds NaN.
+pub fn ks_two_sample<T: TreeElem>(a: &AvlTree<T>, b: &AvlTree<T>) -> Option<f64> {
+    let (n_a, n_b) = (a.len(), b.len());
+    if n_a == 0 || n_b == 0 {
+        return None;
+    }
+
+    let (mut cum_a, mut cum_b) = (0, 0);
+    let mut d: f64 = 0.0;
+    merge_counts(a, b, |c_a, c_b| {
+        cum_a += c_a;
+        cum_b += c_b;
+        d = d.max((cum_a as f64 / n_a as f64 - cum_b as f64 / n_b as f64).abs());
+    });
+    Some(d)
+}
+
+/// Return the one-sample Kolmogorov-Smirnov statistic of the sample held in `tree` against `cdf`,
+/// i.e. the maximum distance between the empirical distribution function and `cdf`.
+///
+/// Return `None` if the sample is empty.
+pub fn ks_one_sample<T: TreeElem, F: Fn(T) -> f64>(tree: &AvlTree<T>, cdf: F) -> Option<f64> {
+    let counts = tree.to_vec_counts();
+    let n: usize = counts.iter().map(|&(_, count)| count).sum();
+    if n == 0 {
+        return None;
+    }
+
+    let mut cum = 0;
+    let mut d: f64 = 0.0;
+    for (value, count) in counts {
+        let f = cdf(value);
+        let below = cum as f64 / n as f64;
+        cum += count;
+        let above = cum as f64 / n as f64;
+        d = d.max(f - below).max(above - f);
+    }
+    Some(d)
+}
@@ -26,11 +26,36 @@ impl TreeElem for u128 {}
 impl TreeElem for usize {}
 impl<T: TreeElem> TreeElem for OrdEqElem<T> {}
 
+/// Data attached to the nodes of AVL tree.
+pub trait NodeData: Default {
+    /// Recompute the summary of the tree at the bottom from this data and the data of the children.
+    /// 
+    /// This is called whenever the node or its children change.
+    fn refresh(&mut self, _left: Option<&Self>, _right: Option<&Self>) {}
+
+    /// Return the number of elements held by this data, if the data decides it.
+    ///
+    /// If this is `Some` and not zero, the number of duplicates of the node is always derived from it.
+    /// Empty data leaves the number as it is.
+    fn count(&self) -> Option<usize> {
+        None
+    }
+}
+
+impl NodeData for () {}
+impl<V> NodeData for Option<V> {}
+
+impl<V> NodeData for Vec<V> {
+    fn count(&self) -> Option<usize> {
+        Some(self.len())
+    }
+}
+
 /// Integer and float with `Ord` and `Eq` trait.
 /// 
 /// When comparing two values, if they are equal,
 /// the order is determined randomly.
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Debug)]
 pub struct OrdEqElem<T: TreeElem> {
     pub value: T,
 }
@@ -96,4 +121,24 @@ impl<T: TreeElem> Zero for OrdEqElem<T> {
     fn is_zero(&self) -> bool {
         self.value == T::zero()
     }
+}
+
+/// Determine if `value` is equal to itself, i.e. not NaN.
+#[allow(clippy::eq_op)]
+pub(crate) fn is_comparable<T: PartialEq>(value: &T) -> bool {
+    value == value
+}
+
+/// Compare `a` with `b`, where values which are not comparable like NaN are `Greater`.
+///
+/// `<` of `OrdEqElem` is random for equal values, so `==` is compared first.
+/// The values must not be NaN if they are `OrdEqElem`, whose `<` panics on it.
+pub(crate) fn cmp_elem<T: TreeElem>(a: T, b: T) -> Ordering {
+    if a == b {
+        Ordering::Equal
+    } else if a < b {
+        Ordering::Less
+    } else {
+        Ordering::Greater
+    }
 }
\ No newline at end of file
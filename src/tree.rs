@@ -1,24 +1,140 @@
 //! AVL tree.
  
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashSet};
+use std::fmt;
+use std::iter::FromIterator;
+use std::ops::{Bound, RangeBounds};
+use std::sync::{Arc, Mutex};
+
+use crate::error::{AvlError, InvariantReport};
 use crate::node::AvlNode;
-use crate::traits::TreeElem;
+use crate::traits::{cmp_elem, TreeElem};
 
 /// AVL tree.
 pub struct AvlTree<T> {
     /// Root node.
     pub root: Option<AvlNode<T>>,
+    /// The maximum number of elements and which element to evict when it is exceeded.
+    limit: Option<(usize, Evict)>,
+}
+
+/// Which element to evict when a tree with a capacity limit is full.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Evict {
+    /// Evict the minimum value.
+    Min,
+    /// Evict the maximum value.
+    Max,
+}
+
+/// Candidate of the most frequent values.
+/// 
+/// If `node` is `None`, this is `value` with `dup` duplicates.
+/// Otherwise, this is the tree at `node` whose values are larger than or equal to `value`
+/// and have at most `dup` duplicates.
+struct Frequent<T> {
+    dup: usize,
+    value: T,
+    node: Option<Arc<Mutex<AvlNode<T>>>>,
+}
+
+impl<T: TreeElem> Frequent<T> {
+    fn from_node(node: &Arc<Mutex<AvlNode<T>>>) -> Self {
+        let n = node.lock().unwrap();
+        Self { dup: n.max_dup, value: n.min_child(), node: Some(node.clone()) }
+    }
+}
+
+impl<T: TreeElem> Ord for Frequent<T> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.dup.cmp(&other.dup)
+            .then_with(|| other.value.partial_cmp(&self.value).unwrap_or(Ordering::Equal))
+            .then_with(|| other.node.is_some().cmp(&self.node.is_some()))
+    }
+}
+
+impl<T: TreeElem> PartialOrd for Frequent<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T: TreeElem> PartialEq for Frequent<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<T: TreeElem> Eq for Frequent<T> {}
+
+impl<T: TreeElem> Default for AvlTree<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: TreeElem> FromIterator<T> for AvlTree<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        let mut tree = Self::new();
+        for value in iter {
+            tree.push(value);
+        }
+        tree
+    }
 }
 
 impl<T: TreeElem> AvlTree<T> {
     /// Create an empty AVL tree.
     pub fn new() -> Self {
         let root = None;
-        Self { root }
+        Self { root, limit: None }
+    }
+
+    /// Create an empty AVL tree holding at most `n` elements.
+    /// 
+    /// When a push makes the tree exceed `n` elements, the minimum or the maximum value is evicted.
+    /// 
+    /// # Panics
+    /// 
+    /// Panics if `n` is zero.
+    pub fn with_capacity_limit(n: usize, evict: Evict) -> Self {
+        assert!(n > 0, "the capacity limit must be positive");
+        Self { root: None, limit: Some((n, evict)) }
+    }
+
+    /// Create an AVL tree from distinct values and the numbers of them in ascending order in `O(n)`.
+    /// 
+    /// # Panics
+    /// 
+    /// Panics if the values are not strictly ascending or some number is zero.
+    pub fn from_sorted_counts(counts: &[(T, usize)]) -> Self {
+        for (i, &(value, count)) in counts.iter().enumerate() {
+            assert!(count > 0, "the number of {} is zero", value);
+            assert!(i == 0 || counts[i - 1].0 < value, "the values are not strictly ascending at {}", value);
+        }
+        let root = AvlNode::build_child(counts).map(|(node, _, _)| node);
+        Self { root, limit: None }
+    }
+
+    /// Return the capacity limit and which element to evict.
+    pub fn capacity_limit(&self) -> Option<(usize, Evict)> {
+        self.limit
     }
 
     /// Push `value` and return the rank and the number of duplication of it.
+    /// 
+    /// If the tree has a capacity limit, an element may be evicted; see `push_evict`.
     pub fn push(&mut self, value: T) -> (usize, usize) {
-        match &mut self.root {
+        self.push_evict(value).0
+    }
+
+    /// Push `value` and return the rank and the number of duplication of it, and the evicted value.
+    /// 
+    /// The rank and the number of duplication are counted after the eviction.
+    /// If `value` itself is evicted, the evicted value is `value` and they are meaningless.
+    pub fn push_evict(&mut self, value: T) -> ((usize, usize), Option<T>) {
+        let (mut rank, mut dup) = match &mut self.root {
             Some(r) => {
                 let (n_ledu, _) = r.push_child(value);
                 n_ledu
@@ -27,16 +143,31 @@ impl<T: TreeElem> AvlTree<T> {
                 self.root = Some(AvlNode::new(value));
                 (0, 0)
             }
+        };
+        self.check(format_args!("push({})", value));
+        let evicted = match self.limit {
+            Some((n, evict)) if self.len() > n => {
+                match evict {
+                    Evict::Min => self.pop_min(),
+                    Evict::Max => self.pop_max(),
+                }
+            }
+            _ => None,
+        };
+        if let Some(e) = evicted {
+            match cmp_elem(e, value) {
+                Ordering::Equal => dup = dup.saturating_sub(1),
+                Ordering::Less => rank -= 1,
+                Ordering::Greater => {}
+            }
         }
+        ((rank, dup), evicted)
     }
 
     /// Determine if `value` exists.
     pub fn isin(&self, value: T) -> bool {
-        match match &self.root {
-            Some(r) => r.search(value),
-            None => None,
-        } {
-            Some(_) => true,
+        match &self.root {
+            Some(r) => r.search(value).is_some(),
             None => false,
         }
     }
@@ -52,80 +183,100 @@ impl<T: TreeElem> AvlTree<T> {
         }
     }
 
+    /// Return the number of elements smaller than `value`.
+    /// 
+    /// `value` does not need to exist in the tree.
+    pub fn rank(&self, value: T) -> usize {
+        match &self.root {
+            Some(r) => r.rank_child(value),
+            None => 0,
+        }
+    }
+
+    /// Return the number of elements smaller than or equal to `value`.
+    pub fn rank_upper(&self, value: T) -> usize {
+        match &self.root {
+            Some(r) => r.rank_upper_child(value),
+            None => 0,
+        }
+    }
+
+    /// Return the empirical cumulative distribution function at `value`,
+    /// i.e. the fraction of elements smaller than or equal to `value`.
+    /// 
+    /// Return `None` if the tree is empty.
+    pub fn ecdf(&self, value: T) -> Option<f64> {
+        match self.len() {
+            0 => None,
+            n => Some(self.rank_upper(value) as f64 / n as f64),
+        }
+    }
+
+    /// Return the `k`-th smallest value (0-indexed).
+    pub fn nth(&self, k: usize) -> Option<T> {
+        match &self.root {
+            Some(r) => r.nth_child(k),
+            None => None,
+        }
+    }
+
     /// Remove `value` from the tree and return the result.
     /// 
     /// If `value` is a duplicate, return and remove only one.
+    #[allow(clippy::result_unit_err)]
     pub fn remove(&mut self, value: T) -> Result<(), ()> {
         match &mut self.root {
             Some(r) => {
-                if r.value == value {
-                    if r.n_ledu.1 > 0 {
-                        r.n_ledu.1 -= 1;
-                        Ok(())
-                    } else {
-                        let res = match (&r.left, &r.right) {
-                            (Some(nl), Some(nr)) => {
-                                let n_m = if r.diff >= 0 {
-                                    nl.lock().unwrap().max_child()
-                                } else {
-                                    nr.lock().unwrap().min_child()
-                                };
-                                r.value = n_m;
-                                r.n_ledu.1 = r.search(n_m).unwrap();
-                                match r.remove_child(n_m) {
-                                    Ok(_) => {}
-                                    Err(()) => panic!(),
-                                }
-                                Ok(None)
-                            }
-                            (Some(nl), None) => {
-                                let nl_lock = nl.lock().unwrap();
-                                r.value = nl_lock.value;
-                                r.diff = nl_lock.diff;
-                                r.n_ledu = nl_lock.n_ledu;
-                                Ok(Some((nl_lock.left.clone(), nl_lock.right.clone())))
-                            }
-                            (None, Some(nr)) => {
-                                let nr_lock = nr.lock().unwrap();
-                                r.value = nr_lock.value;
-                                r.diff = nr_lock.diff;
-                                r.n_ledu = nr_lock.n_ledu;
-                                Ok(Some((nr_lock.left.clone(), nr_lock.right.clone())))
-                            }
-                            (None, None) => {
-                                Err(())
-                            }
-                        };
-                        match res {
-                            Ok(Some((nl_op, nr_op))) => {
-                                r.left = nl_op;
-                                r.right = nr_op;
-                            }
-                            Ok(None) => {}
-                            Err(()) => {
-                                self.root = None;
-                            }
+                if r.remove_child(value)?.is_none() {
+                    self.root = None;
+                }
+                self.check(format_args!("remove({})", value));
+                Ok(())
+            }
+            None => Err(())
+        }
+    }
+
+    /// Replace one of `old` with `new`,
+    /// and return the rank of `old`, and the rank and the number of duplication of `new`.
+    /// 
+    /// If `old` does not exist, the tree is not changed.
+    pub fn replace(&mut self, old: T, new: T) -> Result<(usize, usize, usize), AvlError> {
+        let r = self.root.as_mut().ok_or(AvlError::NotFound)?;
+        // The rank of `old` is found while removing it.
+        let (old_rank, _, _, d_diff) = r.remove_n_data_child(old, 1).map_err(|()| AvlError::NotFound)?;
+        if d_diff.is_none() {
+            self.root = None;
+        }
+        self.check(format_args!("remove({}) in replace", old));
+        let (new_rank, new_dup) = self.push(new);
+        Ok((old_rank, new_rank, new_dup))
+    }
+
+    /// Remove `n` of `value` from the tree and return the number of removed elements.
+    /// 
+    /// If there are `n` or fewer of `value`, remove all of them.
+    pub fn remove_n(&mut self, value: T, n: usize) -> usize {
+        match &mut self.root {
+            Some(r) => {
+                match r.remove_n_child(value, n) {
+                    Ok((removed, d_diff)) => {
+                        if d_diff.is_none() {
+                            self.root = None;
                         }
-                        Ok(())
-                    }
-                } else {
-                    let res = r.remove_child(value);
-                    match res {
-                        Ok(_) => Ok(()),
-                        Err(()) => Err(()),
+                        self.check(format_args!("remove_n({}, {})", value, n));
+                        removed
                     }
+                    Err(()) => 0,
                 }
             }
-            None => Err(())
+            None => 0,
         }
     }
 
     /// Return the maximum value in the tree.
     pub fn max(&self) -> Option<T> {
-        match &self.root {
-            Some(r) => Some(r.max_child()),
-            None => None,
-        }
+        self.root.as_ref().map(|r| r.max_child())
     }
 
     /// Return and remove the maximum value.
@@ -133,39 +284,13 @@ impl<T: TreeElem> AvlTree<T> {
     /// If the maximum value is a duplicate, return and remove only one.
     pub fn pop_max(&mut self) -> Option<T> {
         match &mut self.root {
-            Some(r) => { 
-                let res = match &r.right {
-                    Some(_) => true,
-                    None => false,
-                };
-                if res {
-                    let (value, _) = r.pop_max_child().unwrap();
-                    Some(value)
-                } else {
-                    let ret_value = r.value;
-                    if r.n_ledu.1 > 0 {
-                        r.n_ledu.1 -= 1;
-                    } else {
-                        let op = match &r.left {
-                            Some(node) => {
-                                let n = node.lock().unwrap();
-                                Some((n.value, n.diff, n.n_ledu, n.left.clone(), n.right.clone()))
-                            }
-                            None => None,
-                        };
-                        match op {
-                            Some((value, diff, n_ledu, left, right)) => {
-                                r.value = value;
-                                r.diff = diff;
-                                r.n_ledu = n_ledu;
-                                r.left = left;
-                                r.right = right;
-                            }
-                            None => { self.root = None; } 
-                        }
-                    }
-                    Some(ret_value)
+            Some(r) => {
+                let (value, d_diff) = r.pop_max_child();
+                if d_diff.is_none() {
+                    self.root = None;
                 }
+                self.check(format_args!("pop_max() of {}", value));
+                Some(value)
             }
             None => None,
         }
@@ -174,129 +299,199 @@ impl<T: TreeElem> AvlTree<T> {
     /// Return the maximum value and the number of duplication of it, then remove its node.
     pub fn pop_max_all(&mut self) -> Option<(T, usize)> {
         match &mut self.root {
-            Some(r) => { 
-                let res = match &r.right {
-                    Some(_) => true,
-                    None => false,
-                };
-                if res {
-                    let (value, _) = r.pop_max_all_child().unwrap();
-                    Some(value)
-                } else {
-                    let ret_value = (r.value, r.n_ledu.1);
-                    let op = match &r.left {
-                        Some(node) => {
-                            let n = node.lock().unwrap();
-                            Some((n.value, n.diff, n.n_ledu, n.left.clone(), n.right.clone()))
-                        }
-                        None => None,
-                    };
-                    match op {
-                        Some((value, diff, n_ledu, left, right)) => {
-                            r.value = value;
-                            r.diff = diff;
-                            r.n_ledu = n_ledu;
-                            r.left = left;
-                            r.right = right;
-                        }
-                        None => { self.root = None; } 
-                    }
-                    Some(ret_value)
+            Some(r) => {
+                let (value, _, d_diff) = r.pop_max_all_child();
+                if d_diff.is_none() {
+                    self.root = None;
                 }
+                self.check(format_args!("pop_max_all() of {}", value.0));
+                Some(value)
             }
             None => None,
         }
     }
  
-    /// Return the maximum value in the tree.
+    /// Return the minimum value in the tree.
     pub fn min(&self) -> Option<T> {
-        match &self.root {
-            Some(r) => Some(r.min_child()),
-            None => None,
-        }
+        self.root.as_ref().map(|r| r.min_child())
     }
 
-    /// Return and remove the maximum value.
+    /// Return and remove the minimum value.
     /// 
-    /// If the maximum value is a duplicate, return and remove only one.
+    /// If the minimum value is a duplicate, return and remove only one.
     pub fn pop_min(&mut self) -> Option<T> {
         match &mut self.root {
-            Some(r) => { 
-                let res = match &r.left {
-                    Some(_) => true,
-                    None => false,
-                };
-                if res {
-                    let (value, _) = r.pop_min_child().unwrap();
-                    Some(value)
-                } else {
-                    let ret_value = r.value;
-                    if r.n_ledu.1 > 0 {
-                        r.n_ledu.1 -= 1;
-                    } else {
-                        let op = match &r.right {
-                            Some(node) => {
-                                let n = node.lock().unwrap();
-                                Some((n.value, n.diff, n.n_ledu, n.left.clone(), n.right.clone()))
-                            }
-                            None => None,
-                        };
-                        match op {
-                            Some((value, diff, n_ledu, left, right)) => {
-                                r.value = value;
-                                r.diff = diff;
-                                r.n_ledu = n_ledu;
-                                r.left = left;
-                                r.right = right;
-                            }
-                            None => { self.root = None; } 
-                        }
-                    }
-                    Some(ret_value)
+            Some(r) => {
+                let (value, d_diff) = r.pop_min_child();
+                if d_diff.is_none() {
+                    self.root = None;
                 }
+                self.check(format_args!("pop_min() of {}", value));
+                Some(value)
             }
             None => None,
         }
     }
 
-    /// Return the maximum value and the number of duplication of it, then remove its node.
+    /// Return the minimum value and the number of duplication of it, then remove its node.
     pub fn pop_min_all(&mut self) -> Option<(T, usize)> {
         match &mut self.root {
             Some(r) => {
-                let res = match &r.left {
-                    Some(_) => true,
-                    None => false,
-                };
-                if res {
-                    let (value, _) = r.pop_min_all_child().unwrap();
-                    Some(value)
-                } else {
-                    let ret_value = (r.value, r.n_ledu.1);
-                    let op = match &r.right {
-                        Some(node) => {
-                            let n = node.lock().unwrap();
-                            Some((n.value, n.diff, n.n_ledu, n.left.clone(), n.right.clone()))
-                        }
-                        None => None,
-                    };
-                    match op {
-                        Some((value, diff, n_ledu, left, right)) => {
-                            r.value = value;
-                            r.diff = diff;
-                            r.n_ledu = n_ledu;
-                            r.left = left;
-                            r.right = right;
-                        }
-                        None => { self.root = None; } 
-                    }
-                    Some(ret_value)
+                let (value, _, d_diff) = r.pop_min_all_child();
+                if d_diff.is_none() {
+                    self.root = None;
                 }
+                self.check(format_args!("pop_min_all() of {}", value.0));
+                Some(value)
             }
             None => None,
         }
     }
 
-    /// Return the number of elements.
+    /// Return the most frequent value and the number of it.
+    /// 
+    /// If some values are equally frequent, return the smallest.
+    pub fn mode(&self) -> Option<(T, usize)> {
+        self.root.as_ref().map(|r| r.mode_child())
+    }
+
+    /// Return the `k` most frequent values and the numbers of them in descending order of the numbers.
+    /// 
+    /// Equally frequent values are in ascending order.
+    pub fn top_k_frequent(&self, k: usize) -> Vec<(T, usize)> {
+        let mut top = Vec::with_capacity(k);
+        let r = match &self.root {
+            Some(r) => r,
+            None => return top,
+        };
+        let mut heap = BinaryHeap::new();
+        heap.push(Frequent { dup: r.n_ledu.1, value: r.value, node: None });
+        for node in r.left.iter().chain(r.right.iter()) {
+            heap.push(Frequent::from_node(node));
+        }
+        while top.len() < k {
+            match heap.pop() {
+                Some(Frequent { dup, value, node: None }) => top.push((value, dup + 1)),
+                Some(Frequent { node: Some(node), .. }) => {
+                    let n = node.lock().unwrap();
+                    heap.push(Frequent { dup: n.n_ledu.1, value: n.value, node: None });
+                    for child in n.left.iter().chain(n.right.iter()) {
+                        heap.push(Frequent::from_node(child));
+                    }
+                }
+                None => break,
+            }
+        }
+        top
+    }
+
+    /// Remove all the elements and return them in ascending order.
+    pub fn drain(&mut self) -> std::vec::IntoIter<T> {
+        let v = self.to_vec();
+        self.root = None;
+        v.into_iter()
+    }
+
+    /// Remove the elements in `range` and return them in ascending order.
+    ///
+    /// Only the values in `range` are visited, unless so many are removed that the tree is rebuilt.
+    pub fn drain_range<R: RangeBounds<T>>(&mut self, range: R) -> std::vec::IntoIter<T> {
+        let mut cursor = match range.start_bound() {
+            Bound::Included(&lo) => self.lower_bound_cursor(lo),
+            Bound::Excluded(&lo) => self.upper_bound_cursor(lo),
+            Bound::Unbounded => self.cursor_first(),
+        };
+        let mut removed = Vec::new();
+        while let Some((value, _, dup)) = cursor.current() {
+            if !range.contains(&value) {
+                break;
+            }
+            removed.push((value, dup + 1));
+            cursor.move_next();
+        }
+        if removed.len() * self.height() >= self.distinct_len() {
+            return self.extract_if(|value| range.contains(value));
+        }
+        let mut v = Vec::new();
+        for (value, count) in removed {
+            self.remove_n(value, count);
+            v.resize(v.len() + count, value);
+        }
+        v.into_iter()
+    }
+
+    /// Keep only the values for which `f(value, number_of_value)` returns `true`.
+    pub fn retain<F: FnMut(&T, usize) -> bool>(&mut self, mut f: F) {
+        self.remove_counts(|value, count| !f(value, count));
+    }
+
+    /// Remove the elements for which `pred(value)` returns `true` and return them in ascending order.
+    pub fn extract_if<F: FnMut(&T) -> bool>(&mut self, mut pred: F) -> std::vec::IntoIter<T> {
+        let mut v = Vec::new();
+        for (value, count) in self.remove_counts(|value, _| pred(value)) {
+            v.resize(v.len() + count, value);
+        }
+        v.into_iter()
+    }
+
+    /// Remove the values for which `f(value, number_of_value)` returns `true`,
+    /// and return them and the numbers of them in ascending order.
+    /// 
+    /// If many values are removed, the tree is rebuilt from the rest in `O(n)`.
+    fn remove_counts<F: FnMut(&T, usize) -> bool>(&mut self, mut f: F) -> Vec<(T, usize)> {
+        let mut removed = Vec::new();
+        let mut kept = Vec::new();
+        for (value, count) in self.to_vec_counts() {
+            if f(&value, count) {
+                removed.push((value, count));
+            } else {
+                kept.push((value, count));
+            }
+        }
+        if removed.len() * self.height() < kept.len() + removed.len() {
+            for &(value, count) in removed.iter() {
+                self.remove_n(value, count);
+            }
+        } else if !removed.is_empty() {
+            self.root = AvlNode::build_child(&kept).map(|(node, _, _)| node);
+            self.check(format_args!("rebuilding from {} values", kept.len()));
+        }
+        removed
+    }
+
+    /// Return the elements in ascending order.
+    pub fn to_vec(&self) -> Vec<T> {
+        let mut v = Vec::with_capacity(self.len());
+        for (value, count) in self.to_vec_counts() {
+            v.resize(v.len() + count, value);
+        }
+        v
+    }
+
+    /// Return the distinct values and the numbers of them in ascending order.
+    pub fn to_vec_counts(&self) -> Vec<(T, usize)> {
+        let mut v = Vec::new();
+        if let Some(r) = &self.root {
+            r.collect_child(&mut v);
+        }
+        v
+    }
+
+    /// Return the distinct values and the numbers of them in ascending order, merging equal values.
+    ///
+    /// `OrdEqElem` keeps equal values in separate nodes, which are merged here.
+    pub(crate) fn merged_counts(&self) -> Vec<(T, usize)> {
+        let mut counts: Vec<(T, usize)> = Vec::new();
+        for (value, count) in self.to_vec_counts() {
+            match counts.last_mut() {
+                Some(last) if last.0 == value => last.1 += count,
+                _ => counts.push((value, count)),
+            }
+        }
+        counts
+    }
+
+    /// Return the number of elements in `O(1)`.
     pub fn len(&self) -> usize {
         match &self.root {
             Some(r) => r.len_child_and_self(),
@@ -304,11 +499,70 @@ impl<T: TreeElem> AvlTree<T> {
         }
     }
 
-    /// Return the maximum height of the tree.
+    /// Return the number of distinct values in `O(1)`.
+    pub fn distinct_len(&self) -> usize {
+        match &self.root {
+            Some(r) => r.n_nodes,
+            None => 0,
+        }
+    }
+
+    /// Determine if the tree has no elements.
+    pub fn is_empty(&self) -> bool {
+        self.root.is_none()
+    }
+
+    /// Return the maximum height of the tree in `O(1)`.
     pub fn height(&self) -> usize {
         match &self.root {
             Some(r) => r.height_child(),
             None => 0,
         }
     }
+
+    /// Check the invariants of all the nodes and report the first broken one.
+    ///
+    /// The values must be in order, every `diff` must be the actual difference of heights in `[-1, 1]`,
+    /// `n_ledu.0`, `max_dup` and the cached sizes and height must match the children, and no node may be shared.
+    pub fn validate(&self) -> Result<(), InvariantReport> {
+        match &self.root {
+            Some(r) => r.validate_child(None, None, &mut Vec::new(), &mut HashSet::new()).map(|_| ()),
+            None => Ok(()),
+        }
+    }
+
+    /// Validate the tree after `op` with the `paranoid` feature, and panic with the report if it is broken.
+    #[cfg(feature = "paranoid")]
+    pub(crate) fn check(&self, op: fmt::Arguments<'_>) {
+        if let Err(report) = self.validate() {
+            panic!("{} broke the tree: {}", op, report);
+        }
+    }
+
+    #[cfg(not(feature = "paranoid"))]
+    #[inline(always)]
+    pub(crate) fn check(&self, _op: fmt::Arguments<'_>) {}
+
+    /// Return the tree in Graphviz DOT.
+    ///
+    /// Each node shows `value`, `diff` and `n_ledu`, and each edge is labeled `L` or `R`.
+    pub fn to_dot(&self) -> String {
+        let mut out = String::from("digraph AvlTree {\n    node [shape=box];\n");
+        if let Some(r) = &self.root {
+            r.dot_child(0, &mut out);
+        }
+        out.push_str("}\n");
+        out
+    }
+}
+
+impl<T: TreeElem> fmt::Display for AvlTree<T> {
+    /// Draw the tree sideways, with the root at the left and larger values above.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut out = String::new();
+        if let Some(r) = &self.root {
+            r.draw_child("", "", "", &mut out);
+        }
+        f.write_str(&out)
+    }
 }
\ No newline at end of file
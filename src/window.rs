@@ -0,0 +1,177 @@
+//! Sliding window over a stream of values.
+
+use num_traits::ToPrimitive;
+
+use std::collections::VecDeque;
+
+use crate::traits::{is_comparable, TreeElem};
+use crate::tree::AvlTree;
+
+/// How long the values stay in the window.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Extent {
+    /// Keep the last `n` values.
+    Count(usize),
+    /// Keep the values pushed at time `t` with `now - t < horizon`.
+    Horizon(u64),
+}
+
+/// Sliding window holding the values in AVL tree.
+///
+/// Expired values are removed from the tree automatically.
+pub struct SlidingWindow<T> {
+    extent: Extent,
+    /// The values in the window and the times at which they were pushed, in push order.
+    values: VecDeque<(u64, T)>,
+    tree: AvlTree<T>,
+    /// The latest time.
+    now: u64,
+}
+
+impl<T: TreeElem> SlidingWindow<T> {
+    /// Create a window keeping the last `n` values.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `n` is zero.
+    pub fn new(n: usize) -> Self {
+        assert!(n > 0, "the window must hold at least one value");
+        Self::with_extent(Extent::Count(n))
+    }
+
+    /// Create a window keeping the values pushed within the last `horizon` units of time.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `horizon` is zero.
+    pub fn with_horizon(horizon: u64) -> Self {
+        assert!(horizon > 0, "the horizon must be positive");
+        Self::with_extent(Extent::Horizon(horizon))
+    }
+
+    fn with_extent(extent: Extent) -> Self {
+        Self { extent, values: VecDeque::new(), tree: AvlTree::new(), now: 0 }
+    }
+
+    /// Return the extent of the window.
+    pub fn extent(&self) -> Extent {
+        self.extent
+    }
+
+    /// Push `value` at the next sequence number.
+    ///
+    /// The sequence number is the latest time plus one,
+    /// so a window with a horizon keeps the last `horizon` values pushed by this method.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `value` is NaN.
+    pub fn push(&mut self, value: T) {
+        self.push_at(self.now + 1, value);
+    }
+
+    /// Push `value` at `time`, then remove the expired values.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `time` is earlier than the latest time, or `value` is NaN.
+    pub fn push_at(&mut self, time: u64, value: T) {
+        // NaN could not be found in the tree to be removed when it expires.
+        assert!(is_comparable(&value), "NaN cannot be pushed to the window");
+        self.advance(time);
+        self.values.push_back((time, value));
+        self.tree.push(value);
+        self.expire();
+    }
+
+    /// Move the latest time to `time` and remove the expired values.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `time` is earlier than the latest time.
+    pub fn advance(&mut self, time: u64) {
+        assert!(time >= self.now, "time must not go backwards: {} < {}", time, self.now);
+        self.now = time;
+        self.expire();
+    }
+
+    /// Remove the expired values from the tree.
+    fn expire(&mut self) {
+        loop {
+            let expired = match (self.extent, self.values.front()) {
+                (Extent::Count(n), Some(_)) => self.values.len() > n,
+                (Extent::Horizon(horizon), Some(&(time, _))) => self.now - time >= horizon,
+                (_, None) => false,
+            };
+            if !expired {
+                break;
+            }
+            let (_, value) = self.values.pop_front().unwrap();
+            self.tree.remove(value).expect("every value in the window is in the tree");
+        }
+    }
+
+    /// Return the number of values in the window.
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    /// Determine if the window has no values.
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// Return the tree of the values in the window.
+    pub fn tree(&self) -> &AvlTree<T> {
+        &self.tree
+    }
+
+    /// Return the number of values smaller than `value` in the window.
+    pub fn rank(&self, value: T) -> usize {
+        self.tree.rank(value)
+    }
+
+    /// Count the number of `value` in the window.
+    pub fn count(&self, value: T) -> usize {
+        self.tree.count(value)
+    }
+
+    /// Return the minimum value in the window.
+    pub fn min(&self) -> Option<T> {
+        self.tree.min()
+    }
+
+    /// Return the maximum value in the window.
+    pub fn max(&self) -> Option<T> {
+        self.tree.max()
+    }
+}
+
+impl<T: TreeElem + ToPrimitive> SlidingWindow<T> {
+    /// Return the `q`-quantile of the values in the window.
+    ///
+    /// The quantile is linearly interpolated between the two nearest values.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `q` is not in `[0, 1]`.
+    pub fn quantile(&self, q: f64) -> Option<f64> {
+        assert!((0.0..=1.0).contains(&q), "q must be in [0, 1]: {}", q);
+        if self.is_empty() {
+            return None;
+        }
+        let h = (self.len() - 1) as f64 * q;
+        let lo = h.floor();
+        let x_lo = self.tree.nth(lo as usize)?.to_f64()?;
+        if h == lo {
+            return Some(x_lo);
+        }
+        let x_hi = self.tree.nth(lo as usize + 1)?.to_f64()?;
+        Some(x_lo + (h - lo) * (x_hi - x_lo))
+    }
+
+    /// Return the median of the values in the window.
+    pub fn median(&self) -> Option<f64> {
+        self.quantile(0.5)
+    }
+}
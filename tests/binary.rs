@@ -0,0 +1,137 @@
+//! Tests of the binary format of `AvlTree`.
+
+use avlsort::binary::{BinaryElem, MAGIC, VERSION};
+use avlsort::error::DecodeError;
+use avlsort::traits::OrdEqElem;
+use avlsort::tree::AvlTree;
+
+fn encode<T: BinaryElem>(tree: &AvlTree<T>) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    tree.write_to(&mut bytes).unwrap();
+    bytes
+}
+
+/// Encode `counts` by hand with the header of `T`, without checking them.
+fn encode_counts<T: BinaryElem>(counts: &[(T, u64)]) -> Vec<u8> {
+    let mut bytes = MAGIC.to_vec();
+    bytes.extend_from_slice(&[VERSION, T::TYPE_TAG]);
+    bytes.extend_from_slice(&(counts.len() as u64).to_le_bytes());
+    for (value, count) in counts {
+        value.write_elem(&mut bytes).unwrap();
+        bytes.extend_from_slice(&count.to_le_bytes());
+    }
+    bytes
+}
+
+#[test]
+fn i32_round_trip() {
+    for values in [vec![], vec![7], vec![5, -3, 5, i32::MAX, i32::MIN, 0, 5, -3, 12]] {
+        let tree: AvlTree<_> = values.iter().copied().collect();
+        let bytes = encode(&tree);
+        // Header, the number of distinct values, and a value and its count for each.
+        assert_eq!(bytes.len(), 6 + 8 + tree.to_vec_counts().len() * (4 + 8));
+        let read = AvlTree::<i32>::read_from(bytes.as_slice()).unwrap();
+        assert_eq!(read.to_vec_counts(), tree.to_vec_counts());
+        assert_eq!(read.len(), values.len());
+        read.validate().unwrap();
+    }
+}
+
+#[test]
+fn f64_round_trip() {
+    let values = [2.5, f64::NEG_INFINITY, -1e-300, 2.5, f64::INFINITY, f64::MAX, 0.0, 2.5];
+    let tree: AvlTree<_> = values.iter().copied().collect();
+    let read = AvlTree::<f64>::read_from(encode(&tree).as_slice()).unwrap();
+    assert_eq!(read.to_vec_counts(), tree.to_vec_counts());
+    read.validate().unwrap();
+}
+
+#[test]
+fn ord_eq_elem_round_trip_merges_equal_values() {
+    let values: Vec<OrdEqElem<f64>> = [1.0, 3.0, 1.0, 1.0, 2.0].iter().map(|&x| OrdEqElem::new(x)).collect();
+    let bytes = encode(&values.iter().copied().collect());
+    assert_eq!(bytes[5], 0x80 | f64::TYPE_TAG);
+    let read = AvlTree::<OrdEqElem<f64>>::read_from(bytes.as_slice()).unwrap();
+    let counts: Vec<(f64, usize)> = read.to_vec_counts().into_iter().map(|(x, n)| (x.value, n)).collect();
+    let mut merged: Vec<(f64, usize)> = Vec::new();
+    for (x, n) in counts {
+        match merged.last_mut() {
+            Some(last) if last.0 == x => last.1 += n,
+            _ => merged.push((x, n)),
+        }
+    }
+    assert_eq!(merged, [(1.0, 3), (2.0, 1), (3.0, 1)]);
+}
+
+#[test]
+fn reject_bad_magic() {
+    let mut bytes = encode(&[1, 2].iter().copied().collect());
+    bytes[0] = b'X';
+    assert!(matches!(AvlTree::<i32>::read_from(bytes.as_slice()), Err(DecodeError::BadMagic)));
+}
+
+#[test]
+fn reject_unsupported_version() {
+    let mut bytes = encode(&[1, 2].iter().copied().collect());
+    bytes[4] = VERSION + 1;
+    let err = AvlTree::<i32>::read_from(bytes.as_slice()).err().unwrap();
+    assert!(matches!(err, DecodeError::UnsupportedVersion(v) if v == VERSION + 1));
+}
+
+#[test]
+fn reject_wrong_type_tag() {
+    let bytes = encode(&[1, 2].iter().copied().collect());
+    let err = AvlTree::<u32>::read_from(bytes.as_slice()).err().unwrap();
+    assert!(matches!(err, DecodeError::TypeMismatch { expected: 11, found: 5 }));
+    let err = AvlTree::<OrdEqElem<i32>>::read_from(bytes.as_slice()).err().unwrap();
+    assert!(matches!(err, DecodeError::TypeMismatch { expected: 0x85, found: 5 }));
+}
+
+#[test]
+fn reject_truncated_input() {
+    let bytes = encode(&[3, 1, 4, 1, 5].iter().copied().collect());
+    // Cut in the magic bytes, the header, the number of distinct values, a value and a count.
+    for len in [0, 2, 5, 10, 14, 16, 20, bytes.len() - 1] {
+        let err = AvlTree::<i32>::read_from(&bytes[..len]).err().unwrap();
+        assert!(matches!(err, DecodeError::Truncated), "{}: {:?}", len, err);
+    }
+}
+
+#[test]
+fn reject_unsorted_values() {
+    let bytes = encode_counts(&[(1, 2), (5, 1), (3, 1)]);
+    assert!(matches!(AvlTree::<i32>::read_from(bytes.as_slice()), Err(DecodeError::Unsorted(2))));
+    // Equal values must be merged.
+    let bytes = encode_counts(&[(1, 2), (1, 1)]);
+    assert!(matches!(AvlTree::<i32>::read_from(bytes.as_slice()), Err(DecodeError::Unsorted(1))));
+    let bytes = encode_counts(&[(OrdEqElem::new(1), 2), (OrdEqElem::new(1), 1)]);
+    assert!(matches!(AvlTree::<OrdEqElem<i32>>::read_from(bytes.as_slice()), Err(DecodeError::Unsorted(1))));
+}
+
+#[test]
+fn reject_zero_count() {
+    let bytes = encode_counts(&[(1, 2), (5, 0), (7, 1)]);
+    assert!(matches!(AvlTree::<i32>::read_from(bytes.as_slice()), Err(DecodeError::ZeroCount(1))));
+}
+
+#[test]
+fn reject_nan() {
+    let bytes = encode_counts(&[(f64::NAN, 1)]);
+    assert!(matches!(AvlTree::<f64>::read_from(bytes.as_slice()), Err(DecodeError::Unsorted(0))));
+    let bytes = encode_counts(&[(1.0, 1), (f64::NAN, 1), (2.0, 1)]);
+    assert!(matches!(AvlTree::<f64>::read_from(bytes.as_slice()), Err(DecodeError::Unsorted(1))));
+    let bytes = encode_counts(&[(OrdEqElem::new(f64::NAN), 1)]);
+    assert!(matches!(AvlTree::<OrdEqElem<f64>>::read_from(bytes.as_slice()), Err(DecodeError::Unsorted(0))));
+
+    // A tree holding NaN can be written, but is rejected when it is read.
+    let tree: AvlTree<_> = [f64::NAN].iter().copied().collect();
+    assert!(matches!(AvlTree::<f64>::read_from(encode(&tree).as_slice()), Err(DecodeError::Unsorted(_))));
+}
+
+#[test]
+fn reject_too_many_elements() {
+    let bytes = encode_counts(&[(1, u64::MAX), (2, 1)]);
+    let err = AvlTree::<i32>::read_from(bytes.as_slice()).err().unwrap();
+    assert!(matches!(err, DecodeError::Overflow), "{:?}", err);
+    assert_eq!(err.to_string(), "a number in the input is out of range");
+}
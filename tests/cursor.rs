@@ -0,0 +1,98 @@
+//! Tests of `Cursor` and `CursorMut`.
+
+use avlsort::tree::AvlTree;
+
+#[test]
+fn cursor_moves_over_duplicates() {
+    let tree: AvlTree<_> = [5, 1, 3, 3, 5, 5, 7, 1, 5].iter().copied().collect();
+    let mut cursor = tree.cursor_first();
+    let mut forward = Vec::new();
+    while let Some(current) = cursor.current() {
+        forward.push(current);
+        cursor.move_next();
+    }
+    // `(value, rank, number_of_duplicates)` of each distinct value.
+    assert_eq!(forward, [(1, 0, 1), (3, 2, 1), (5, 4, 3), (7, 8, 0)]);
+
+    // From the ghost position, move to the other end.
+    cursor.move_prev();
+    let mut backward = Vec::new();
+    while let Some(current) = cursor.current() {
+        backward.push(current);
+        cursor.move_prev();
+    }
+    backward.reverse();
+    assert_eq!(backward, forward);
+    cursor.move_next();
+    assert_eq!(cursor.current(), Some((1, 0, 1)));
+}
+
+#[test]
+fn cursor_bounds_and_nth_with_duplicates() {
+    let tree: AvlTree<_> = [5, 1, 3, 3, 5, 5, 7, 1, 5].iter().copied().collect();
+    assert_eq!(tree.lower_bound_cursor(3).current(), Some((3, 2, 1)));
+    assert_eq!(tree.upper_bound_cursor(3).current(), Some((5, 4, 3)));
+    assert_eq!(tree.lower_bound_cursor(4).current(), Some((5, 4, 3)));
+    assert_eq!(tree.lower_bound_cursor(8).current(), None);
+    assert_eq!(tree.upper_bound_cursor(7).current(), None);
+    assert_eq!(tree.cursor_last().current(), Some((7, 8, 0)));
+    for (k, &value) in tree.to_vec().iter().enumerate() {
+        let (v, rank, dup) = tree.nth_cursor(k).current().unwrap();
+        assert_eq!(v, value);
+        assert!(rank <= k && k <= rank + dup);
+    }
+    assert_eq!(tree.nth_cursor(9).current(), None);
+    assert_eq!(AvlTree::<i32>::new().cursor_first().current(), None);
+}
+
+#[test]
+fn cursor_mut_insert_stays_at_current() {
+    let mut tree: AvlTree<_> = [2, 4, 4, 6].iter().copied().collect();
+    let mut cursor = tree.lower_bound_cursor_mut(4);
+    assert_eq!(cursor.insert(1), (0, 0));
+    assert_eq!(cursor.current(), Some((4, 2, 1)));
+    assert_eq!(cursor.insert(4), (2, 2));
+    assert_eq!(cursor.current(), Some((4, 2, 2)));
+    assert_eq!(cursor.insert(9), (6, 0));
+    cursor.move_next();
+    assert_eq!(cursor.current(), Some((6, 5, 0)));
+    assert_eq!(tree.to_vec(), [1, 2, 4, 4, 4, 6, 9]);
+    tree.validate().unwrap();
+
+    // At the ghost position, the cursor stays there.
+    let mut cursor = tree.upper_bound_cursor_mut(9);
+    cursor.insert(10);
+    assert_eq!(cursor.current(), None);
+    cursor.move_prev();
+    assert_eq!(cursor.current(), Some((10, 7, 0)));
+}
+
+#[test]
+fn cursor_mut_remove_current() {
+    let mut tree: AvlTree<_> = [2, 4, 4, 6].iter().copied().collect();
+    let mut cursor = tree.lower_bound_cursor_mut(4);
+    // A duplicate is left, so the cursor stays at 4.
+    assert_eq!(cursor.remove_current(), Some(4));
+    assert_eq!(cursor.current(), Some((4, 1, 0)));
+    // No duplicate is left, so the cursor moves to the next larger value.
+    assert_eq!(cursor.remove_current(), Some(4));
+    assert_eq!(cursor.current(), Some((6, 1, 0)));
+    assert_eq!(cursor.remove_current(), Some(6));
+    assert_eq!(cursor.current(), None);
+    assert_eq!(cursor.remove_current(), None);
+    assert_eq!(cursor.as_tree().to_vec(), [2]);
+    tree.validate().unwrap();
+}
+
+#[test]
+fn cursor_mut_remove_current_all() {
+    let mut tree: AvlTree<_> = [2, 4, 4, 4, 6].iter().copied().collect();
+    let mut cursor = tree.lower_bound_cursor_mut(3);
+    assert_eq!(cursor.remove_current_all(), Some((4, 2)));
+    assert_eq!(cursor.current(), Some((6, 1, 0)));
+    cursor.move_prev();
+    assert_eq!(cursor.remove_current_all(), Some((2, 0)));
+    assert_eq!(cursor.current(), Some((6, 0, 0)));
+    assert_eq!(tree.to_vec(), [6]);
+    tree.validate().unwrap();
+}
@@ -0,0 +1,70 @@
+//! Snapshot tests of the DOT export and the `Display` of `AvlTree`.
+
+use avlsort::tree::AvlTree;
+
+#[test]
+fn display_draws_tree_sideways() {
+    let tree: AvlTree<_> = [1, 2, 3, 4, 5, 6, 7].iter().copied().collect();
+    let expected = concat!(
+        "    ┌── 7 (diff=0, less=0, dup=0)\n",
+        "┌── 6 (diff=0, less=1, dup=0)\n",
+        "│   └── 5 (diff=0, less=0, dup=0)\n",
+        "4 (diff=0, less=3, dup=0)\n",
+        "│   ┌── 3 (diff=0, less=0, dup=0)\n",
+        "└── 2 (diff=0, less=1, dup=0)\n",
+        "    └── 1 (diff=0, less=0, dup=0)\n",
+    );
+    assert_eq!(tree.to_string(), expected);
+}
+
+#[test]
+fn display_shows_duplicates_and_diff() {
+    let tree: AvlTree<_> = [2, 1, 2, 3].iter().copied().collect();
+    let expected = concat!(
+        "┌── 3 (diff=0, less=0, dup=0)\n",
+        "2 (diff=0, less=1, dup=1)\n",
+        "└── 1 (diff=0, less=0, dup=0)\n",
+    );
+    assert_eq!(tree.to_string(), expected);
+
+    let tree: AvlTree<_> = [1, 2].iter().copied().collect();
+    assert_eq!(tree.to_string(), "┌── 2 (diff=0, less=0, dup=0)\n1 (diff=-1, less=0, dup=0)\n");
+    assert_eq!(AvlTree::<i32>::new().to_string(), "");
+}
+
+#[test]
+fn dot_lists_nodes_in_preorder() {
+    let tree: AvlTree<_> = [1, 2, 3, 4, 5, 6, 7].iter().copied().collect();
+    let expected = r#"digraph AvlTree {
+    node [shape=box];
+    n0 [label="4 (diff=0, less=3, dup=0)"];
+    n0 -> n1 [label="L"];
+    n1 [label="2 (diff=0, less=1, dup=0)"];
+    n1 -> n2 [label="L"];
+    n2 [label="1 (diff=0, less=0, dup=0)"];
+    n1 -> n3 [label="R"];
+    n3 [label="3 (diff=0, less=0, dup=0)"];
+    n0 -> n4 [label="R"];
+    n4 [label="6 (diff=0, less=1, dup=0)"];
+    n4 -> n5 [label="L"];
+    n5 [label="5 (diff=0, less=0, dup=0)"];
+    n4 -> n6 [label="R"];
+    n6 [label="7 (diff=0, less=0, dup=0)"];
+}
+"#;
+    assert_eq!(tree.to_dot(), expected);
+}
+
+#[test]
+fn dot_of_small_trees() {
+    let expected = r#"digraph AvlTree {
+    node [shape=box];
+    n0 [label="1 (diff=-1, less=0, dup=0)"];
+    n0 -> n1 [label="R"];
+    n1 [label="2 (diff=0, less=0, dup=0)"];
+}
+"#;
+    let tree: AvlTree<_> = [1, 2].iter().copied().collect();
+    assert_eq!(tree.to_dot(), expected);
+    assert_eq!(AvlTree::<i32>::new().to_dot(), "digraph AvlTree {\n    node [shape=box];\n}\n");
+}
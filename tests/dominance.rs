@@ -0,0 +1,50 @@
+//! Tests of the dominance counts against brute force.
+
+use avlsort::dominance::{dominance_counts, DominanceIndex};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+/// Count the points of `points` dominated by `(x, y)` in `O(n)`.
+fn brute_count(points: &[(i32, i32)], x: i32, y: i32) -> usize {
+    points.iter().filter(|&&(px, py)| px <= x && py <= y).count()
+}
+
+#[test]
+fn dominance_counts_match_brute_force() {
+    let mut rng = StdRng::seed_from_u64(41);
+    for _ in 0..200 {
+        let range = rng.gen_range(1..12);
+        let points: Vec<(i32, i32)> = (0..rng.gen_range(0..40)).map(|_| (rng.gen_range(0..range), rng.gen_range(0..range))).collect();
+        let expected: Vec<usize> = points.iter().map(|&(x, y)| brute_count(&points, x, y)).collect();
+        assert_eq!(dominance_counts(&points), expected, "{:?}", points);
+    }
+}
+
+#[test]
+fn index_matches_brute_force() {
+    let mut rng = StdRng::seed_from_u64(41);
+    for _ in 0..200 {
+        let range = rng.gen_range(1..12);
+        let points: Vec<(i32, i32)> = (0..rng.gen_range(0..40)).map(|_| (rng.gen_range(0..range), rng.gen_range(0..range))).collect();
+        let index = DominanceIndex::new(&points);
+        assert_eq!((index.len(), index.is_empty()), (points.len(), points.is_empty()));
+        // The queries are also outside the points.
+        for x in -1..=range {
+            for y in -1..=range {
+                assert_eq!(index.count(x, y), brute_count(&points, x, y), "{:?} at ({}, {})", points, x, y);
+            }
+        }
+    }
+}
+
+#[test]
+#[should_panic(expected = "NaN cannot be sorted")]
+fn dominance_counts_reject_nan() {
+    dominance_counts(&[(1.0, 2.0), (0.5, f64::NAN)]);
+}
+
+#[test]
+#[should_panic(expected = "NaN cannot be indexed")]
+fn index_rejects_nan() {
+    DominanceIndex::new(&[(1.0, 2.0), (0.5, f64::NAN)]);
+}
@@ -0,0 +1,78 @@
+//! Tests of removing many elements from `AvlTree` at once.
+
+use std::ops::{Bound, RangeBounds};
+
+use avlsort::tree::AvlTree;
+
+const VALUES: [i32; 12] = [5, 1, 3, 3, 9, 5, 7, 1, 5, 2, 8, 8];
+
+#[test]
+fn drain_empties_the_tree() {
+    let mut tree: AvlTree<_> = VALUES.iter().copied().collect();
+    let mut expected = VALUES.to_vec();
+    expected.sort_unstable();
+    assert_eq!(tree.drain().collect::<Vec<_>>(), expected);
+    assert!(tree.is_empty());
+    assert_eq!(tree.drain().next(), None);
+}
+
+#[test]
+fn drain_range_bounds() {
+    let ranges: [(Bound<i32>, Bound<i32>); 7] = [
+        (Bound::Unbounded, Bound::Unbounded),
+        (Bound::Included(3), Bound::Excluded(8)),
+        (Bound::Included(3), Bound::Included(8)),
+        (Bound::Excluded(3), Bound::Included(8)),
+        (Bound::Excluded(1), Bound::Unbounded),
+        (Bound::Unbounded, Bound::Excluded(3)),
+        (Bound::Included(4), Bound::Included(4)),
+    ];
+    for &range in ranges.iter() {
+        let mut tree: AvlTree<_> = VALUES.iter().copied().collect();
+        let mut kept = VALUES.to_vec();
+        kept.sort_unstable();
+        let drained: Vec<i32> = kept.iter().copied().filter(|x| range.contains(x)).collect();
+        kept.retain(|x| !range.contains(x));
+        assert_eq!(tree.drain_range(range).collect::<Vec<_>>(), drained, "{:?}", range);
+        assert_eq!(tree.to_vec(), kept, "{:?}", range);
+        tree.validate().unwrap();
+    }
+}
+
+#[test]
+fn retain_sees_counts() {
+    let mut tree: AvlTree<_> = VALUES.iter().copied().collect();
+    tree.retain(|&value, count| count > 1 && value != 8);
+    assert_eq!(tree.to_vec(), [1, 1, 3, 3, 5, 5, 5]);
+    tree.validate().unwrap();
+    tree.retain(|_, _| false);
+    assert!(tree.is_empty());
+}
+
+#[test]
+fn extract_if_returns_removed() {
+    let mut tree: AvlTree<_> = VALUES.iter().copied().collect();
+    assert_eq!(tree.extract_if(|&value| value % 2 == 1).collect::<Vec<_>>(), [1, 1, 3, 3, 5, 5, 5, 7, 9]);
+    assert_eq!(tree.to_vec(), [2, 8, 8]);
+    tree.validate().unwrap();
+    assert_eq!(tree.extract_if(|_| false).next(), None);
+    assert_eq!(tree.len(), 3);
+}
+
+#[test]
+fn removal_below_and_above_rebuild_threshold() {
+    // The tree is rebuilt once `removed * height >= distinct`, so remove 1 to 1000 values of 1000.
+    for &n_removed in [1, 10, 99, 100, 101, 500, 999, 1000].iter() {
+        let values: Vec<i32> = (0..1000).flat_map(|x| vec![x; 1 + (x % 3) as usize]).collect();
+        let mut tree: AvlTree<_> = values.iter().copied().collect();
+        let drained: Vec<i32> = tree.drain_range(..n_removed).collect();
+        assert_eq!(drained, values[..values.partition_point(|&x| x < n_removed)]);
+        assert_eq!(tree.distinct_len(), 1000 - n_removed as usize);
+        tree.validate().unwrap();
+
+        let mut tree: AvlTree<_> = values.iter().copied().collect();
+        tree.retain(|&x, _| x >= n_removed);
+        assert_eq!(tree.to_vec(), values[values.partition_point(|&x| x < n_removed)..]);
+        tree.validate().unwrap();
+    }
+}
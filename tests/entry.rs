@@ -0,0 +1,107 @@
+//! Tests of the entry API of `AvlTree`.
+
+use std::collections::BTreeMap;
+
+use avlsort::entry::Entry;
+use avlsort::tree::{AvlTree, Evict};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+#[test]
+fn insert_on_vacant_entry() {
+    let mut tree: AvlTree<_> = [1, 5, 5, 9].iter().copied().collect();
+    let entry = tree.entry(3);
+    assert!(matches!(entry, Entry::Vacant(_)));
+    assert_eq!((entry.rank(), entry.count()), (1, 0));
+    let entry = entry.insert();
+    assert!(matches!(entry, Entry::Occupied(_)));
+    assert_eq!((entry.value(), entry.rank(), entry.count()), (3, 1, 1));
+
+    match tree.entry(7) {
+        Entry::Vacant(e) => {
+            let entry = e.insert();
+            assert_eq!((entry.rank(), entry.count()), (4, 1));
+        }
+        Entry::Occupied(_) => panic!("7 is not in the tree"),
+    }
+    assert_eq!(tree.to_vec(), [1, 3, 5, 5, 7, 9]);
+    tree.validate().unwrap();
+
+    // Inserting nothing leaves the entry vacant.
+    assert!(matches!(tree.entry(4).increment(0), Entry::Vacant(_)));
+    let mut empty = AvlTree::new();
+    assert_eq!(empty.entry(4).insert().count(), 1);
+    assert_eq!(empty.to_vec(), [4]);
+}
+
+#[test]
+fn decrement_to_zero_becomes_vacant() {
+    let mut tree: AvlTree<_> = [1, 5, 5, 5, 9].iter().copied().collect();
+    let entry = tree.entry(5).decrement(2);
+    assert!(matches!(entry, Entry::Occupied(_)));
+    assert_eq!((entry.rank(), entry.count()), (1, 1));
+    let entry = entry.decrement(1);
+    assert!(matches!(entry, Entry::Vacant(_)));
+    assert_eq!((entry.rank(), entry.count()), (1, 0));
+    // Decrementing a vacant entry does nothing.
+    assert!(matches!(entry.decrement(3), Entry::Vacant(_)));
+    assert_eq!(tree.to_vec(), [1, 9]);
+    tree.validate().unwrap();
+
+    // Removing more than the count removes all of them.
+    let entry = tree.entry(9).increment(2).decrement(10);
+    assert!(matches!(entry, Entry::Vacant(_)));
+    assert_eq!(tree.to_vec(), [1]);
+    match tree.entry(1) {
+        Entry::Occupied(e) => assert_eq!(e.remove().1, 1),
+        Entry::Vacant(_) => panic!("1 is in the tree"),
+    }
+    assert!(tree.is_empty());
+}
+
+#[test]
+fn counts_along_path_match_model() {
+    let mut rng = StdRng::seed_from_u64(0);
+    let mut tree = AvlTree::new();
+    let mut model: BTreeMap<i32, usize> = BTreeMap::new();
+    for _ in 0..2000 {
+        let value = rng.gen_range(0..50);
+        let n = rng.gen_range(0..4);
+        let entry = if rng.gen_bool(0.6) {
+            *model.entry(value).or_insert(0) += n;
+            tree.entry(value).increment(n)
+        } else {
+            let count = model.get(&value).copied().unwrap_or(0);
+            if count <= n {
+                model.remove(&value);
+            } else {
+                model.insert(value, count - n);
+            }
+            tree.entry(value).decrement(n)
+        };
+        model.retain(|_, &mut count| count > 0);
+        let rank: usize = model.range(..value).map(|(_, &count)| count).sum();
+        assert_eq!((entry.rank(), entry.count()), (rank, model.get(&value).copied().unwrap_or(0)));
+        tree.validate().unwrap();
+        for (&v, &count) in model.iter() {
+            assert_eq!(tree.count(v), count);
+        }
+        assert_eq!(tree.len(), model.values().sum::<usize>());
+        assert_eq!(tree.mode().map(|(_, n)| n), model.values().copied().max());
+    }
+}
+
+#[test]
+fn increment_evicts_over_capacity() {
+    let mut tree = AvlTree::with_capacity_limit(4, Evict::Min);
+    for &value in [1, 2, 3].iter() {
+        tree.push(value);
+    }
+    let entry = tree.entry(2).increment(2);
+    assert_eq!((entry.rank(), entry.count()), (0, 3));
+    assert_eq!(tree.to_vec(), [2, 2, 2, 3]);
+    // The value itself may be evicted.
+    let entry = tree.entry(0).increment(1);
+    assert_eq!(entry.count(), 0);
+    assert_eq!(tree.to_vec(), [2, 2, 2, 3]);
+}
@@ -0,0 +1,98 @@
+//! Tests of `AvlIntervalTree` against brute force.
+
+use avlsort::interval::AvlIntervalTree;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+/// Return the intervals of `model` overlapping `[lo, hi]` in ascending order.
+///
+/// No interval overlaps if `lo > hi`.
+fn brute_overlapping(model: &[(i32, i32)], lo: i32, hi: i32) -> Vec<(i32, i32)> {
+    if lo > hi {
+        return Vec::new();
+    }
+    let mut v: Vec<(i32, i32)> = model.iter().copied().filter(|&(l, h)| l <= hi && lo <= h).collect();
+    v.sort_unstable();
+    v
+}
+
+/// Check the queries of `tree` against `model` for `[lo, hi]`.
+fn assert_queries(tree: &AvlIntervalTree<i32>, model: &[(i32, i32)], lo: i32, hi: i32) {
+    let expected = brute_overlapping(model, lo, hi);
+    // The intervals with the same `lo` are not ordered by `hi`.
+    let mut actual: Vec<(i32, i32)> = tree.overlapping(lo, hi).collect();
+    actual.sort_unstable();
+    assert_eq!(actual, expected, "[{}, {}]", lo, hi);
+    assert_eq!(tree.count_overlapping(lo, hi), expected.len(), "[{}, {}]", lo, hi);
+}
+
+#[test]
+fn touching_endpoints_overlap() {
+    let model = [(1, 3), (3, 5), (5, 5), (7, 9)];
+    let tree: AvlIntervalTree<_> = model.iter().copied().collect();
+    for (lo, hi) in [(3, 3), (5, 5), (5, 7), (0, 1), (9, 12), (6, 6), (10, 12), (4, 4), (6, 7)] {
+        assert_queries(&tree, &model, lo, hi);
+    }
+    assert_eq!(tree.stabbing(5).collect::<Vec<_>>(), [(3, 5), (5, 5)]);
+    assert_eq!(tree.count_overlapping(4, 2), 0);
+}
+
+#[test]
+fn duplicate_intervals() {
+    let mut tree: AvlIntervalTree<_> = [(2, 4), (2, 4), (2, 6), (2, 4)].iter().copied().collect();
+    assert_eq!(tree.count_overlapping(4, 4), 4);
+    assert_eq!(tree.remove(2, 4), Ok(()));
+    assert_eq!(tree.len(), 3);
+    assert_queries(&tree, &[(2, 4), (2, 4), (2, 6)], 3, 3);
+    assert_eq!(tree.remove(2, 4), Ok(()));
+    assert_eq!(tree.remove(2, 4), Ok(()));
+    assert_eq!(tree.remove(2, 4), Err(()));
+    assert_queries(&tree, &[(2, 6)], 0, 10);
+    assert_eq!(tree.remove(2, 6), Ok(()));
+    assert!(tree.is_empty());
+    assert_eq!(tree.count_overlapping(0, 10), 0);
+}
+
+#[test]
+fn remove_absent_interval() {
+    let model = [(1, 3), (2, 8), (5, 6)];
+    let mut tree: AvlIntervalTree<_> = model.iter().copied().collect();
+    // Neither the start nor the pair exists.
+    assert_eq!(tree.remove(4, 6), Err(()));
+    assert_eq!(tree.remove(2, 3), Err(()));
+    assert_eq!(tree.remove(5, 8), Err(()));
+    assert_eq!(tree.len(), 3);
+    for (lo, hi) in [(0, 10), (3, 3), (7, 9)] {
+        assert_queries(&tree, &model, lo, hi);
+    }
+    assert_eq!(AvlIntervalTree::new().remove(1, 2), Err(()));
+}
+
+/// Apply random insertions and removals and compare the queries with brute force.
+#[test]
+fn random_ops_match_brute_force() {
+    let mut rng = StdRng::seed_from_u64(40);
+    let mut tree = AvlIntervalTree::new();
+    let mut model: Vec<(i32, i32)> = Vec::new();
+    for _ in 0..2000 {
+        let lo = rng.gen_range(0..30);
+        let hi = lo + rng.gen_range(0..8);
+        if rng.gen_bool(0.6) {
+            tree.insert(lo, hi);
+            model.push((lo, hi));
+        } else {
+            let expected = match model.iter().position(|&i| i == (lo, hi)) {
+                Some(i) => {
+                    model.swap_remove(i);
+                    Ok(())
+                }
+                None => Err(()),
+            };
+            assert_eq!(tree.remove(lo, hi), expected);
+        }
+        assert_eq!(tree.len(), model.len());
+        let lo = rng.gen_range(-2..40);
+        let hi = lo + rng.gen_range(-1..6);
+        assert_queries(&tree, &model, lo, hi);
+    }
+}
@@ -0,0 +1,79 @@
+//! Tests of the longest increasing subsequences against `O(n^2)` dynamic programming.
+
+use avlsort::lis::{lis_count, lis_indices, lis_length, lnds_indices, lnds_length};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+/// Return the length of the longest subsequence of `v` whose adjacent elements satisfy `follows`,
+/// and the number of such subsequences by their indices.
+fn brute<F: Fn(i32, i32) -> bool>(v: &[i32], follows: F) -> (usize, u64) {
+    // The length and the number of the longest subsequences ending at each element.
+    let mut ends: Vec<(usize, u64)> = Vec::with_capacity(v.len());
+    for j in 0..v.len() {
+        let mut end = (1, 1);
+        for i in 0..j {
+            if follows(v[i], v[j]) {
+                if ends[i].0 + 1 > end.0 {
+                    end = (ends[i].0 + 1, ends[i].1);
+                } else if ends[i].0 + 1 == end.0 {
+                    end.1 += ends[i].1;
+                }
+            }
+        }
+        ends.push(end);
+    }
+    let length = ends.iter().map(|&(length, _)| length).max().unwrap_or(0);
+    let count = ends.iter().filter(|&&(l, _)| l == length).map(|&(_, count)| count).sum();
+    (length, if v.is_empty() { 0 } else { count })
+}
+
+/// Assert that `indices` are ascending and the elements at them satisfy `follows`.
+fn assert_subsequence<F: Fn(i32, i32) -> bool>(v: &[i32], indices: &[usize], follows: F) {
+    for w in indices.windows(2) {
+        assert!(w[0] < w[1] && follows(v[w[0]], v[w[1]]), "{:?}: {:?}", v, indices);
+    }
+    assert!(indices.iter().all(|&i| i < v.len()), "{:?}: {:?}", v, indices);
+}
+
+#[test]
+fn lis_matches_brute_force() {
+    let mut rng = StdRng::seed_from_u64(42);
+    for _ in 0..500 {
+        let range = rng.gen_range(1..20);
+        let v: Vec<i32> = (0..rng.gen_range(0..30)).map(|_| rng.gen_range(0..range)).collect();
+
+        let (length, count) = brute(&v, |a, b| a < b);
+        assert_eq!(lis_length(&v), length, "{:?}", v);
+        assert_eq!(lis_count(&v), count, "{:?}", v);
+        let indices = lis_indices(&v);
+        assert_eq!(indices.len(), length, "{:?}", v);
+        assert_subsequence(&v, &indices, |a, b| a < b);
+
+        let (length, _) = brute(&v, |a, b| a <= b);
+        assert_eq!(lnds_length(&v), length, "{:?}", v);
+        let indices = lnds_indices(&v);
+        assert_eq!(indices.len(), length, "{:?}", v);
+        assert_subsequence(&v, &indices, |a, b| a <= b);
+    }
+}
+
+#[test]
+fn lis_count_saturates() {
+    // Each pair `(2k + 1, 2k)` doubles the number of the subsequences, so there are `2^70` of them.
+    let v: Vec<i32> = (0..70).flat_map(|k| [2 * k + 1, 2 * k]).collect();
+    assert_eq!(lis_length(&v), 70);
+    assert_eq!(lis_count(&v), u64::MAX);
+    assert_eq!(lis_count::<i32>(&[]), 0);
+}
+
+#[test]
+#[should_panic(expected = "NaN cannot be ordered")]
+fn lis_rejects_nan() {
+    lis_length(&[1.0, f64::NAN, 2.0]);
+}
+
+#[test]
+#[should_panic(expected = "NaN cannot be ordered")]
+fn lnds_rejects_nan() {
+    lnds_indices(&[1.0, f64::NAN, 2.0]);
+}
@@ -0,0 +1,126 @@
+//! Tests of `AvlMap` and `AvlMultiMap`.
+
+use std::collections::BTreeMap;
+use std::ops::Bound;
+
+use avlsort::map::{AvlMap, AvlMultiMap};
+use avlsort::node::AvlNode;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+#[test]
+fn map_returns_clones() {
+    let mut map = AvlMap::new();
+    for (key, s) in [(3, "c"), (1, "a"), (4, "d"), (2, "b")] {
+        map.insert(key, s.to_string());
+    }
+    assert_eq!(map.get(4), Some("d".to_string()));
+    assert_eq!(map.get(5), None);
+    assert_eq!(map.get_with(3, |value| value.len()), Some(1));
+
+    let pairs: Vec<(i32, String)> = map.range(2..4).collect();
+    assert_eq!(pairs, [(2, "b".to_string()), (3, "c".to_string())]);
+    let keys: Vec<i32> = map.iter().map(|(key, _)| key).collect();
+    assert_eq!(keys, [1, 2, 3, 4]);
+}
+
+/// Reading the map while holding a value or iterating over it must not wait for a lock of the map itself.
+#[test]
+fn reads_do_not_hold_locks() {
+    let mut map = AvlMap::new();
+    let mut multi = AvlMultiMap::new();
+    for key in 0..50 {
+        map.insert(key, key * 10);
+        multi.insert(key % 7, key);
+    }
+    let value = map.get(1).unwrap();
+    assert!(map.contains_key(1));
+    assert_eq!(map.get(1), Some(value));
+    for (key, value) in map.iter() {
+        assert_eq!(map.get(key), Some(value));
+        assert_eq!(map.rank(key), key as usize);
+    }
+    for (key, value) in multi.range(2..5) {
+        assert!(multi.get(key).unwrap().contains(&value));
+        assert!(multi.contains_key(key));
+    }
+}
+
+#[test]
+fn multimap_returns_clones() {
+    let mut map = AvlMultiMap::new();
+    assert_eq!(map.insert(2, 'x'), (0, 0));
+    assert_eq!(map.insert(1, 'y'), (0, 0));
+    assert_eq!(map.insert(2, 'z'), (1, 1));
+    assert_eq!(map.get(2), Some(vec!['x', 'z']));
+    assert_eq!(map.get(3), None);
+    assert_eq!(map.iter().collect::<Vec<_>>(), [(1, 'y'), (2, 'x'), (2, 'z')]);
+    assert_eq!(map.range(2..).collect::<Vec<_>>(), [(2, 'x'), (2, 'z')]);
+}
+
+#[test]
+fn multimap_count_follows_values() {
+    let mut map = AvlMultiMap::new();
+    for (key, value) in [(5, 'a'), (3, 'b'), (5, 'c'), (8, 'd'), (5, 'e'), (3, 'f')] {
+        map.insert(key, value);
+    }
+    assert_eq!((map.count(3), map.count(5), map.count(8), map.count(4)), (2, 3, 1, 0));
+    assert_eq!((map.rank(5), map.rank(8), map.len()), (2, 5, 6));
+    assert_eq!(map.nth(4), Some((5, 'e')));
+
+    assert_eq!(map.remove(5), Some(vec!['a', 'c', 'e']));
+    assert_eq!((map.count(5), map.rank(8), map.len()), (0, 2, 3));
+    assert_eq!(map.remove(5), None);
+}
+
+/// Empty values do not decide the number of duplicates, so the node keeps its own count.
+#[test]
+fn empty_values_keep_count() {
+    let mut node = AvlNode::<i32, Vec<u8>>::new(1);
+    assert_eq!((node.n_ledu, node.len), ((0, 0), 1));
+    let node2 = AvlNode::<i32, Vec<u8>>::with_count(2, 3);
+    assert_eq!((node2.n_ledu, node2.len), ((0, 2), 3));
+
+    node.push_child(1);
+    node.push_child(0);
+    assert_eq!((node.n_ledu, node.len), ((1, 1), 3));
+    assert_eq!(node.remove_n_data_child(1, 1).map(|(rank, n, _, _)| (rank, n)), Ok((1, 1)));
+    assert_eq!((node.n_ledu, node.len), ((1, 0), 2));
+}
+
+/// Compare the maps with `BTreeMap` after random insertions and removals.
+#[test]
+fn maps_match_btreemap() {
+    let mut rng = StdRng::seed_from_u64(38);
+    let mut map = AvlMap::new();
+    let mut multi = AvlMultiMap::new();
+    let mut model: BTreeMap<i32, u32> = BTreeMap::new();
+    let mut multi_model: BTreeMap<i32, Vec<u32>> = BTreeMap::new();
+    for i in 0..2000 {
+        let key = rng.gen_range(0..50);
+        if rng.gen_bool(0.7) {
+            assert_eq!(map.insert(key, i), model.insert(key, i));
+            multi.insert(key, i);
+            multi_model.entry(key).or_default().push(i);
+        } else {
+            assert_eq!(map.remove(key), model.remove(&key));
+            assert_eq!(multi.remove(key), multi_model.remove(&key));
+        }
+
+        let lo = rng.gen_range(0..50);
+        let hi = rng.gen_range(lo..55);
+        let bounds = (Bound::Excluded(lo), Bound::Included(hi));
+        let actual: Vec<(i32, u32)> = map.range(bounds).collect();
+        let expected: Vec<(i32, u32)> = model.range(bounds).map(|(&k, &v)| (k, v)).collect();
+        assert_eq!(actual, expected);
+
+        let actual: Vec<(i32, u32)> = multi.range(lo..hi).collect();
+        let expected: Vec<(i32, u32)> = multi_model.range(lo..hi).flat_map(|(&k, v)| v.iter().map(move |&x| (k, x))).collect();
+        assert_eq!(actual, expected);
+        let rank: usize = multi_model.range(..key).map(|(_, v)| v.len()).sum();
+        assert_eq!(multi.rank(key), rank);
+        assert_eq!(multi.count(key), multi_model.get(&key).map_or(0, |v| v.len()));
+    }
+    assert_eq!(map.len(), model.len());
+    assert_eq!(multi.len(), multi_model.values().map(|v| v.len()).sum::<usize>());
+}
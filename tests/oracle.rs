@@ -0,0 +1,317 @@
+//! Differential tests of `AvlTree` against a sorted `Vec`.
+//!
+//! Random sequences of operations are applied to both, and every result is compared after each step.
+//! A failing sequence is shrunk before it is reported with its seed.
+//! Set `AVLSORT_SEED` to replay a seed.
+
+use std::panic::{self, AssertUnwindSafe};
+
+use avlsort::tree::AvlTree;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+/// Operations applied to the tree and the model.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Op {
+    Push(i32),
+    Remove(i32),
+    RemoveN(i32, usize),
+    Replace(i32, i32),
+    PopMin,
+    PopMax,
+    PopMinAll,
+    PopMaxAll,
+    Query(i32),
+    Nth(usize),
+}
+
+impl Op {
+    /// Generate an operation with values in `0..range`.
+    fn random(rng: &mut StdRng, range: i32) -> Self {
+        let v = rng.gen_range(0..range);
+        match rng.gen_range(0..16) {
+            0..=5 => Op::Push(v),
+            6 | 7 => Op::Remove(v),
+            8 => Op::RemoveN(v, rng.gen_range(0..4)),
+            9 => Op::Replace(v, rng.gen_range(0..range)),
+            10 => Op::PopMin,
+            11 => Op::PopMax,
+            12 => Op::PopMinAll,
+            13 => Op::PopMaxAll,
+            14 => Op::Query(v),
+            _ => Op::Nth(rng.gen_range(0..range as usize)),
+        }
+    }
+
+    /// Return the operations simpler than this one.
+    fn simpler(self) -> Vec<Self> {
+        let smaller = |v: i32| if v > 0 { vec![0, v / 2, v - 1] } else { Vec::new() };
+        match self {
+            Op::Push(v) => smaller(v).into_iter().map(Op::Push).collect(),
+            Op::Remove(v) => smaller(v).into_iter().map(Op::Remove).collect(),
+            Op::RemoveN(v, n) => {
+                let mut ops: Vec<Self> = smaller(v).into_iter().map(|v| Op::RemoveN(v, n)).collect();
+                if n > 1 {
+                    ops.push(Op::RemoveN(v, 1));
+                }
+                ops
+            }
+            Op::Replace(old, new) => {
+                let mut ops = vec![Op::Remove(old), Op::Push(new)];
+                ops.extend(smaller(old).into_iter().map(|old| Op::Replace(old, new)));
+                ops.extend(smaller(new).into_iter().map(|new| Op::Replace(old, new)));
+                ops
+            }
+            Op::PopMinAll => vec![Op::PopMin],
+            Op::PopMaxAll => vec![Op::PopMax],
+            Op::Query(v) => smaller(v).into_iter().map(Op::Query).collect(),
+            Op::Nth(k) => if k > 0 { vec![Op::Nth(0), Op::Nth(k / 2)] } else { Vec::new() },
+            Op::PopMin | Op::PopMax => Vec::new(),
+        }
+    }
+}
+
+/// Naive multiset as a sorted `Vec`.
+#[derive(Default)]
+struct Model {
+    v: Vec<i32>,
+}
+
+impl Model {
+    fn rank(&self, value: i32) -> usize {
+        self.v.partition_point(|&x| x < value)
+    }
+
+    fn count(&self, value: i32) -> usize {
+        self.v.partition_point(|&x| x <= value) - self.rank(value)
+    }
+
+    fn push(&mut self, value: i32) -> (usize, usize) {
+        let (rank, dup) = (self.rank(value), self.count(value));
+        self.v.insert(rank + dup, value);
+        (rank, dup)
+    }
+
+    fn remove_n(&mut self, value: i32, n: usize) -> usize {
+        let removed = self.count(value).min(n);
+        let rank = self.rank(value);
+        self.v.drain(rank..rank + removed);
+        removed
+    }
+
+    fn pop_min_all(&mut self) -> Option<(i32, usize)> {
+        let value = *self.v.first()?;
+        Some((value, self.remove_n(value, usize::MAX) - 1))
+    }
+
+    fn pop_max_all(&mut self) -> Option<(i32, usize)> {
+        let value = *self.v.last()?;
+        Some((value, self.remove_n(value, usize::MAX) - 1))
+    }
+
+    fn top_k_frequent(&self, k: usize) -> Vec<(i32, usize)> {
+        let mut counts: Vec<(i32, usize)> = Vec::new();
+        for &x in self.v.iter() {
+            match counts.last_mut() {
+                Some(last) if last.0 == x => last.1 += 1,
+                _ => counts.push((x, 1)),
+            }
+        }
+        counts.sort_by_key(|&(x, count)| (std::cmp::Reverse(count), x));
+        counts.truncate(k);
+        counts
+    }
+}
+
+/// Compare `expected` from the model with `actual` from the tree.
+fn expect_eq<R: PartialEq + std::fmt::Debug>(step: usize, op: Op, expected: R, actual: R) -> Result<(), String> {
+    if expected == actual {
+        Ok(())
+    } else {
+        Err(format!("step {} {:?}: expected {:?}, got {:?}", step, op, expected, actual))
+    }
+}
+
+/// Apply `ops` to the tree and the model, and return the first difference.
+fn run(ops: &[Op]) -> Result<(), String> {
+    let mut tree = AvlTree::new();
+    let mut model = Model::default();
+    for (step, &op) in ops.iter().enumerate() {
+        match op {
+            Op::Push(v) => expect_eq(step, op, model.push(v), tree.push(v))?,
+            Op::Remove(v) => {
+                let expected = if model.remove_n(v, 1) == 1 { Ok(()) } else { Err(()) };
+                expect_eq(step, op, expected, tree.remove(v))?;
+            }
+            Op::RemoveN(v, n) => expect_eq(step, op, model.remove_n(v, n), tree.remove_n(v, n))?,
+            Op::Replace(old, new) => {
+                let expected = if model.count(old) > 0 {
+                    let old_rank = model.rank(old);
+                    model.remove_n(old, 1);
+                    let (new_rank, new_dup) = model.push(new);
+                    Some((old_rank, new_rank, new_dup))
+                } else {
+                    None
+                };
+                expect_eq(step, op, expected, tree.replace(old, new).ok())?;
+            }
+            Op::PopMin => {
+                let expected = model.v.first().copied();
+                if let Some(v) = expected {
+                    model.remove_n(v, 1);
+                }
+                expect_eq(step, op, expected, tree.pop_min())?;
+            }
+            Op::PopMax => expect_eq(step, op, model.v.pop(), tree.pop_max())?,
+            Op::PopMinAll => expect_eq(step, op, model.pop_min_all(), tree.pop_min_all())?,
+            Op::PopMaxAll => expect_eq(step, op, model.pop_max_all(), tree.pop_max_all())?,
+            Op::Query(v) => {
+                expect_eq(step, op, model.count(v), tree.count(v))?;
+                expect_eq(step, op, model.count(v) > 0, tree.isin(v))?;
+                expect_eq(step, op, model.rank(v), tree.rank(v))?;
+                expect_eq(step, op, model.rank(v) + model.count(v), tree.rank_upper(v))?;
+                // `v` as `k` also exceeds the number of distinct values.
+                expect_eq(step, op, model.top_k_frequent(v as usize), tree.top_k_frequent(v as usize))?;
+            }
+            Op::Nth(k) => expect_eq(step, op, model.v.get(k).copied(), tree.nth(k))?,
+        }
+
+        expect_eq(step, op, model.v.len(), tree.len())?;
+        expect_eq(step, op, model.v.is_empty(), tree.is_empty())?;
+        expect_eq(step, op, model.v.first().copied(), tree.min())?;
+        expect_eq(step, op, model.v.last().copied(), tree.max())?;
+        let distinct = model.v.iter().enumerate().filter(|&(i, &x)| i == 0 || model.v[i - 1] != x).count();
+        expect_eq(step, op, distinct, tree.distinct_len())?;
+        // The height of AVL tree with `d` nodes is less than `1.4405 * log2(d + 2)`.
+        let bound = 1.4405 * ((distinct + 2) as f64).log2();
+        if tree.height() as f64 >= bound {
+            return Err(format!("step {} {:?}: height {} of {} nodes is too large", step, op, tree.height(), distinct));
+        }
+        tree.validate().map_err(|report| format!("step {} {:?}: {}", step, op, report))?;
+    }
+    let elements = tree.to_vec();
+    if model.v == elements {
+        Ok(())
+    } else {
+        Err(format!("at the end: expected {:?}, got {:?}", model.v, elements))
+    }
+}
+
+/// Run `ops` with `check`, treating a panic as a failure.
+fn fails<F: Fn(&[Op]) -> Result<(), String>>(check: &F, ops: &[Op]) -> Option<String> {
+    match panic::catch_unwind(AssertUnwindSafe(|| check(ops))) {
+        Ok(Ok(())) => None,
+        Ok(Err(message)) => Some(message),
+        Err(_) => Some(String::from("panicked")),
+    }
+}
+
+/// Shrink failing `ops` by removing chunks of operations and simplifying each of them
+/// until no smaller sequence fails.
+fn shrink<F: Fn(&[Op]) -> Result<(), String>>(check: &F, mut ops: Vec<Op>) -> (Vec<Op>, String) {
+    let mut message = fails(check, &ops).expect("the sequence does not fail");
+    loop {
+        let mut shrunk = false;
+        let mut chunk = ops.len() / 2;
+        while chunk > 0 {
+            let mut start = 0;
+            while start + chunk <= ops.len() {
+                let mut candidate = ops.clone();
+                candidate.drain(start..start + chunk);
+                match fails(check, &candidate) {
+                    Some(m) => {
+                        ops = candidate;
+                        message = m;
+                        shrunk = true;
+                    }
+                    None => start += chunk,
+                }
+            }
+            chunk /= 2;
+        }
+        for i in 0..ops.len() {
+            for simpler in ops[i].simpler() {
+                let mut candidate = ops.clone();
+                candidate[i] = simpler;
+                if let Some(m) = fails(check, &candidate) {
+                    ops = candidate;
+                    message = m;
+                    shrunk = true;
+                    break;
+                }
+            }
+        }
+        if !shrunk {
+            return (ops, message);
+        }
+    }
+}
+
+/// Return the seeds to run, which are `AVLSORT_SEED` if set.
+fn seeds(n: u64) -> Vec<u64> {
+    match std::env::var("AVLSORT_SEED") {
+        Ok(seed) => vec![seed.parse().expect("AVLSORT_SEED must be an integer")],
+        Err(_) => (0..n).collect(),
+    }
+}
+
+/// Run random sequences of `len` operations with values in `0..range` for each seed.
+fn differential(n_seeds: u64, len: usize, range: i32) {
+    for seed in seeds(n_seeds) {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let ops: Vec<Op> = (0..len).map(|_| Op::random(&mut rng, range)).collect();
+        if fails(&run, &ops).is_some() {
+            let (ops, message) = shrink(&run, ops);
+            panic!("seed {} failed: {}\nshrunk to {} operations: {:?}", seed, message, ops.len(), ops);
+        }
+    }
+}
+
+#[test]
+fn differential_many_duplicates() {
+    differential(200, 300, 8);
+}
+
+#[test]
+fn differential_few_duplicates() {
+    differential(200, 300, 1000);
+}
+
+#[test]
+fn differential_long() {
+    differential(10, 5000, 200);
+}
+
+#[test]
+fn shrink_finds_minimal_sequence() {
+    // A property broken once the tree holds 3 copies of some value.
+    let check = |ops: &[Op]| {
+        let mut tree = AvlTree::new();
+        for &op in ops {
+            if let Op::Push(v) = op {
+                tree.push(v);
+            }
+            if matches!(tree.mode(), Some((_, n)) if n >= 3) {
+                return Err(String::from("too many duplicates"));
+            }
+        }
+        Ok(())
+    };
+    let mut rng = StdRng::seed_from_u64(0);
+    let ops: Vec<Op> = (0..200).map(|_| Op::random(&mut rng, 50)).collect();
+    let (ops, _) = shrink(&check, ops);
+    // Each value is simplified alone, so the three values stay equal but not necessarily zero.
+    assert_eq!(ops.len(), 3);
+    assert!(ops.iter().all(|&op| matches!(op, Op::Push(_)) && op == ops[0]), "{:?}", ops);
+}
+
+#[test]
+fn top_k_frequent_ties_and_bounds() {
+    let tree: AvlTree<_> = [5, 1, 3, 3, 9, 5, 7, 1, 5, 2].iter().copied().collect();
+    // 1 and 3 are equally frequent, so they are in ascending order.
+    assert_eq!(tree.top_k_frequent(3), [(5, 3), (1, 2), (3, 2)]);
+    assert_eq!(tree.top_k_frequent(4), [(5, 3), (1, 2), (3, 2), (2, 1)]);
+    assert_eq!(tree.top_k_frequent(100), [(5, 3), (1, 2), (3, 2), (2, 1), (7, 1), (9, 1)]);
+    assert_eq!(tree.top_k_frequent(0), []);
+    assert_eq!(AvlTree::<i32>::new().top_k_frequent(3), []);
+}
@@ -0,0 +1,62 @@
+//! Regression tests of ties of `OrdEqElem`, whose `<` is random for equal values.
+
+use avlsort::traits::OrdEqElem;
+use avlsort::tree::{AvlTree, Evict};
+
+fn elem(x: f64) -> OrdEqElem<f64> {
+    OrdEqElem::new(x)
+}
+
+/// Evicting a value equal to the pushed one must not lower the rank below zero.
+///
+/// Which of the equal values is evicted is random, so the rank is only bounded.
+#[test]
+fn push_evict_equal_value() {
+    for evict in [Evict::Min, Evict::Max] {
+        for limit in 1..4 {
+            let mut tree = AvlTree::with_capacity_limit(limit, evict);
+            for i in 0..200 {
+                let ((rank, dup), evicted) = tree.push_evict(elem(1.0));
+                assert!(rank + dup <= tree.len(), "{:?} {}: rank={}, dup={}", evict, i, rank, dup);
+                assert_eq!(evicted.is_some(), i >= limit);
+                assert_eq!(tree.len(), (i + 1).min(limit));
+            }
+            tree.validate().unwrap();
+        }
+    }
+}
+
+/// Every copy of an equal value must be found by `remove`, whichever way `<` goes.
+#[test]
+fn remove_every_equal_value() {
+    let mut tree = AvlTree::new();
+    // 101 copies of 1.0, since `i` also takes it.
+    for i in 0..100 {
+        tree.push(elem(1.0));
+        tree.push(elem(i as f64));
+    }
+    for i in 0..100 {
+        assert!(tree.remove(elem(1.0)).is_ok(), "{}", i);
+        tree.validate().unwrap();
+    }
+    assert!(tree.remove(elem(1.0)).is_ok());
+    assert!(tree.remove(elem(1.0)).is_err());
+    assert_eq!(tree.len(), 99);
+}
+
+/// `remove_current` removes the value under the cursor, so it must not miss it.
+///
+/// The cursor starts at the last value, since `lower_bound` of equal values is random.
+#[test]
+fn cursor_removes_equal_values() {
+    let mut tree = AvlTree::new();
+    for _ in 0..100 {
+        tree.push(elem(2.0));
+    }
+    tree.push(elem(1.0));
+    for i in 0..100 {
+        assert_eq!(tree.cursor_last_mut().remove_current().map(|e| e.value), Some(2.0), "{}", i);
+    }
+    assert_eq!(tree.len(), 1);
+    tree.validate().unwrap();
+}
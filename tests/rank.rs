@@ -0,0 +1,96 @@
+//! Tests of the rank transform against the examples of `scipy.stats.rankdata`.
+
+use avlsort::rank::{rankdata, RankMethod, RankStream};
+
+const METHODS: [RankMethod; 5] = [RankMethod::Average, RankMethod::Min, RankMethod::Max, RankMethod::Dense, RankMethod::Ordinal];
+
+#[test]
+fn rankdata_ties() {
+    let data = [0, 2, 3, 2];
+    let expected: [&[f64]; 5] = [
+        &[1.0, 2.5, 4.0, 2.5],
+        &[1.0, 2.0, 4.0, 2.0],
+        &[1.0, 3.0, 4.0, 3.0],
+        &[1.0, 2.0, 3.0, 2.0],
+        &[1.0, 2.0, 4.0, 3.0],
+    ];
+    for (&method, &expected) in METHODS.iter().zip(expected.iter()) {
+        assert_eq!(rankdata(&data, method), expected, "{:?}", method);
+    }
+}
+
+#[test]
+fn rankdata_many_ties() {
+    let data = [3.0, 1.0, 4.0, 1.0, 5.0, 9.0, 2.0, 6.0, 5.0, 3.0, 5.0];
+    let expected: [&[f64]; 5] = [
+        &[4.5, 1.5, 6.0, 1.5, 8.0, 11.0, 3.0, 10.0, 8.0, 4.5, 8.0],
+        &[4.0, 1.0, 6.0, 1.0, 7.0, 11.0, 3.0, 10.0, 7.0, 4.0, 7.0],
+        &[5.0, 2.0, 6.0, 2.0, 9.0, 11.0, 3.0, 10.0, 9.0, 5.0, 9.0],
+        &[3.0, 1.0, 4.0, 1.0, 5.0, 7.0, 2.0, 6.0, 5.0, 3.0, 5.0],
+        &[4.0, 1.0, 6.0, 2.0, 7.0, 11.0, 3.0, 10.0, 8.0, 5.0, 9.0],
+    ];
+    for (&method, &expected) in METHODS.iter().zip(expected.iter()) {
+        assert_eq!(rankdata(&data, method), expected, "{:?}", method);
+    }
+}
+
+#[test]
+fn rankdata_empty() {
+    for &method in METHODS.iter() {
+        assert!(rankdata::<i32>(&[], method).is_empty());
+    }
+}
+
+#[test]
+fn rankdata_nan_propagates() {
+    for &method in METHODS.iter() {
+        let ranks = rankdata(&[1.0, f64::NAN, 0.0], method);
+        assert_eq!(ranks.len(), 3);
+        assert!(ranks.iter().all(|r| r.is_nan()), "{:?}: {:?}", method, ranks);
+    }
+}
+
+#[test]
+fn rank_stream_ties() {
+    let data = [0, 2, 3, 2, 2];
+    let expected: [&[f64]; 5] = [
+        &[1.0, 2.0, 3.0, 2.5, 3.0],
+        &[1.0, 2.0, 3.0, 2.0, 2.0],
+        &[1.0, 2.0, 3.0, 3.0, 4.0],
+        &[1.0, 2.0, 3.0, 2.0, 2.0],
+        &[1.0, 2.0, 3.0, 3.0, 4.0],
+    ];
+    for (&method, &expected) in METHODS.iter().zip(expected.iter()) {
+        let mut stream = RankStream::new(method);
+        let ranks: Vec<f64> = data.iter().map(|&x| stream.push(x)).collect();
+        assert_eq!(ranks, expected, "{:?}", method);
+        assert_eq!(stream.len(), data.len());
+    }
+}
+
+#[test]
+fn rank_stream_rank_does_not_push() {
+    // The ranks which 2 and 4 would have after 0, 2, 3 and 2.
+    let expected = [(3.0, 5.0), (2.0, 5.0), (4.0, 5.0), (2.0, 4.0), (4.0, 5.0)];
+    for (&method, &expected) in METHODS.iter().zip(expected.iter()) {
+        let mut stream = RankStream::new(method);
+        for &x in [0, 2, 3, 2].iter() {
+            stream.push(x);
+        }
+        assert_eq!((stream.rank(2), stream.rank(4)), expected, "{:?}", method);
+        assert_eq!(stream.len(), 4);
+    }
+}
+
+#[test]
+fn rank_stream_skips_nan() {
+    for &method in METHODS.iter() {
+        let mut stream = RankStream::new(method);
+        assert_eq!(stream.push(1.0), 1.0);
+        assert!(stream.push(f64::NAN).is_nan());
+        assert!(stream.rank(f64::NAN).is_nan());
+        assert_eq!(stream.len(), 1);
+        assert_eq!(stream.push(0.0), 1.0, "{:?}", method);
+        assert_eq!(stream.push(2.0), 3.0, "{:?}", method);
+    }
+}
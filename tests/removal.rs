@@ -0,0 +1,61 @@
+//! Regression tests of removal from `AvlTree`.
+
+use avlsort::tree::AvlTree;
+
+#[test]
+fn remove_updates_ranks_of_ancestors() {
+    // 4 is the root, and 1 is removed from its left subtree.
+    let mut tree: AvlTree<_> = [4, 2, 6, 1, 3, 5, 7].iter().copied().collect();
+    tree.remove(1).unwrap();
+    assert_eq!(tree.rank(4), 2);
+    assert_eq!(tree.rank(7), 5);
+    assert_eq!(tree.pop_min(), Some(2));
+    assert_eq!(tree.rank(6), 3);
+    assert_eq!(tree.nth(0), Some(3));
+    tree.validate().unwrap();
+}
+
+#[test]
+fn remove_node_with_two_children_keeps_duplicates_of_neighbour() {
+    // 2 has the children 1 and 3, and the neighbours replacing it have duplicates.
+    for values in [[2, 1, 3, 1, 1], [2, 1, 3, 3, 3]].iter() {
+        let mut tree: AvlTree<_> = values.iter().copied().collect();
+        tree.remove(2).unwrap();
+        assert_eq!(tree.count(1) + tree.count(3), 4, "{:?}", values);
+        assert_eq!(tree.len(), 4);
+        let mut expected = values.to_vec();
+        expected.retain(|&x| x != 2);
+        expected.sort_unstable();
+        assert_eq!(tree.to_vec(), expected);
+        tree.validate().unwrap();
+    }
+}
+
+#[test]
+fn remove_from_right_rebalances() {
+    // Removing 3 leaves the left subtree two levels taller.
+    let mut tree: AvlTree<_> = [2, 1, 3, 0].iter().copied().collect();
+    tree.remove(3).unwrap();
+    assert_eq!(tree.height(), 2);
+    assert_eq!(tree.to_vec(), [0, 1, 2]);
+    tree.validate().unwrap();
+
+    let mut tree: AvlTree<_> = [5, 2, 8, 1, 3, 7, 9, 0, 4, 6].iter().copied().collect();
+    tree.remove(9).unwrap();
+    tree.remove(6).unwrap();
+    assert_eq!(tree.to_vec(), [0, 1, 2, 3, 4, 5, 7, 8]);
+    tree.validate().unwrap();
+}
+
+#[test]
+fn pop_max_all_reports_height_change() {
+    let mut tree: AvlTree<_> = [5, 3, 8, 2, 4, 7, 9, 1, 6, 9, 9].iter().copied().collect();
+    let mut expected = tree.to_vec_counts();
+    while let Some((value, dup)) = tree.pop_max_all() {
+        assert_eq!(Some((value, dup + 1)), expected.pop());
+        assert_eq!(tree.to_vec_counts(), expected);
+        assert_eq!(tree.min(), expected.first().map(|&(x, _)| x));
+        tree.validate().unwrap();
+    }
+    assert!(expected.is_empty());
+}
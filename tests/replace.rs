@@ -0,0 +1,48 @@
+//! Tests of `AvlTree::replace`.
+
+use avlsort::error::AvlError;
+use avlsort::tree::{AvlTree, Evict};
+
+#[test]
+fn replace_returns_ranks() {
+    let mut tree: AvlTree<_> = [1, 3, 3, 5, 7, 9].iter().copied().collect();
+    // `(rank_of_old, rank_of_new, number_of_duplicates_of_new)`
+    assert_eq!(tree.replace(9, 3), Ok((5, 1, 2)));
+    assert_eq!(tree.replace(1, 8), Ok((0, 5, 0)));
+    assert_eq!(tree.replace(3, 0), Ok((0, 0, 0)));
+    assert_eq!(tree.to_vec(), [0, 3, 3, 5, 7, 8]);
+    tree.validate().unwrap();
+
+    let mut tree: AvlTree<_> = [5].iter().copied().collect();
+    assert_eq!(tree.replace(5, 7), Ok((0, 0, 0)));
+    assert_eq!(tree.to_vec(), [7]);
+}
+
+#[test]
+fn replace_with_same_value() {
+    let mut tree: AvlTree<_> = [1, 2, 2, 3].iter().copied().collect();
+    assert_eq!(tree.replace(2, 2), Ok((1, 1, 1)));
+    assert_eq!(tree.replace(3, 3), Ok((3, 3, 0)));
+    assert_eq!(tree.to_vec(), [1, 2, 2, 3]);
+    tree.validate().unwrap();
+}
+
+#[test]
+fn replace_absent_value() {
+    let mut tree: AvlTree<_> = [1, 3, 3, 5].iter().copied().collect();
+    assert_eq!(tree.replace(2, 4), Err(AvlError::NotFound));
+    assert_eq!(tree.replace(6, 4), Err(AvlError::NotFound));
+    assert_eq!(tree.to_vec(), [1, 3, 3, 5]);
+    tree.validate().unwrap();
+    assert_eq!(AvlTree::new().replace(1, 2), Err(AvlError::NotFound));
+}
+
+#[test]
+fn replace_in_full_tree_does_not_evict() {
+    let mut tree = AvlTree::with_capacity_limit(3, Evict::Min);
+    for &value in [1, 2, 3].iter() {
+        tree.push(value);
+    }
+    assert_eq!(tree.replace(2, 0), Ok((1, 0, 0)));
+    assert_eq!(tree.to_vec(), [0, 1, 3]);
+}
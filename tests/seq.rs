@@ -0,0 +1,88 @@
+//! Tests of `AvlSeq` against `Vec`.
+
+use avlsort::seq::AvlSeq;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+fn seq_of(values: &[i32]) -> AvlSeq<i32> {
+    let mut seq = AvlSeq::new();
+    for &value in values {
+        seq.push(value);
+    }
+    seq
+}
+
+/// Check that `seq` is valid and holds the same elements as `model`.
+fn assert_same(seq: &AvlSeq<i32>, model: &[i32]) {
+    seq.validate().unwrap();
+    assert_eq!(seq.len(), model.len());
+    assert_eq!(seq.to_vec(), model);
+}
+
+#[test]
+fn split_and_concat_at_every_index() {
+    let values: Vec<i32> = (0..40).collect();
+    for index in 0..=values.len() + 1 {
+        let (left, right) = seq_of(&values).split_at(index);
+        let mid = index.min(values.len());
+        assert_same(&left, &values[..mid]);
+        assert_same(&right, &values[mid..]);
+        assert_same(&left.concat(right), &values);
+    }
+}
+
+#[test]
+fn concat_trees_of_different_heights() {
+    for n in [0, 1, 2, 5, 17, 100] {
+        let long: Vec<i32> = (0..n).collect();
+        let short = [-1, -2];
+        let mut expected = long.clone();
+        expected.extend_from_slice(&short);
+        assert_same(&seq_of(&long).concat(seq_of(&short)), &expected);
+
+        let mut expected = short.to_vec();
+        expected.extend_from_slice(&long);
+        assert_same(&seq_of(&short).concat(seq_of(&long)), &expected);
+    }
+}
+
+/// Apply random `insert`, `remove`, `split_at` and `concat` to `AvlSeq` and `Vec`.
+#[test]
+fn random_ops_match_vec() {
+    let mut rng = StdRng::seed_from_u64(39);
+    let mut seq = AvlSeq::new();
+    let mut model: Vec<i32> = Vec::new();
+    for i in 0..3000 {
+        match rng.gen_range(0..10) {
+            0..=4 => {
+                let index = rng.gen_range(0..=model.len());
+                seq.insert(index, i);
+                model.insert(index, i);
+            }
+            5..=7 => {
+                let index = rng.gen_range(0..model.len() + 2);
+                let expected = if index < model.len() { Some(model.remove(index)) } else { None };
+                assert_eq!(seq.remove(index), expected);
+            }
+            8 => {
+                // Split, then concatenate the halves in the other order.
+                let index = rng.gen_range(0..model.len() + 2);
+                let (left, right) = seq.split_at(index);
+                let mid = index.min(model.len());
+                assert_same(&left, &model[..mid]);
+                assert_same(&right, &model[mid..]);
+                seq = right.concat(left);
+                model.rotate_left(mid);
+            }
+            _ => {
+                let index = rng.gen_range(0..model.len() + 2);
+                assert_eq!(seq.get(index), model.get(index).copied());
+                if index < model.len() {
+                    seq.get_mut_with(index, |value| *value = -*value);
+                    model[index] = -model[index];
+                }
+            }
+        }
+        assert_same(&seq, &model);
+    }
+}
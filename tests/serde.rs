@@ -0,0 +1,75 @@
+//! Tests of the serde support.
+
+#![cfg(feature = "serde")]
+
+use avlsort::map::{AvlMap, AvlMultiMap};
+use avlsort::traits::OrdEqElem;
+use avlsort::tree::AvlTree;
+
+#[test]
+fn tree_round_trip() {
+    let tree: AvlTree<_> = [5, 1, 5, 3, 9, 1, 5].iter().copied().collect();
+    let json = serde_json::to_string(&tree).unwrap();
+    assert_eq!(json, "[[1,2],[3,1],[5,3],[9,1]]");
+    let read: AvlTree<i32> = serde_json::from_str(&json).unwrap();
+    assert_eq!(read.to_vec_counts(), tree.to_vec_counts());
+    read.validate().unwrap();
+
+    let read: AvlTree<i32> = serde_json::from_str("[]").unwrap();
+    assert!(read.is_empty());
+}
+
+#[test]
+fn ord_eq_elem_tree_merges_equal_values() {
+    let tree: AvlTree<_> = [2.0, 1.0, 2.0, 2.0].iter().map(|&x| OrdEqElem::new(x)).collect();
+    let json = serde_json::to_string(&tree).unwrap();
+    assert_eq!(json, "[[1.0,1],[2.0,3]]");
+    let read: AvlTree<OrdEqElem<f64>> = serde_json::from_str(&json).unwrap();
+    assert_eq!(read.len(), 4);
+    read.validate().unwrap();
+}
+
+#[test]
+fn map_round_trip() {
+    let mut map = AvlMap::new();
+    for (key, value) in [(3, "c"), (1, "a"), (2, "b"), (1, "z")] {
+        map.insert(key, value.to_string());
+    }
+    let json = serde_json::to_string(&map).unwrap();
+    assert_eq!(json, r#"[[1,"z"],[2,"b"],[3,"c"]]"#);
+    let read: AvlMap<i32, String> = serde_json::from_str(&json).unwrap();
+    let pairs: Vec<(i32, String)> = read.iter().collect();
+    assert_eq!(pairs, [(1, "z".to_string()), (2, "b".to_string()), (3, "c".to_string())]);
+}
+
+#[test]
+fn multimap_round_trip() {
+    let mut map = AvlMultiMap::new();
+    for (key, value) in [(2, 'x'), (1, 'y'), (2, 'z'), (5, 'w'), (2, 'v')] {
+        map.insert(key, value);
+    }
+    assert_eq!(map.distinct_len(), 3);
+    let json = serde_json::to_string(&map).unwrap();
+    assert_eq!(json, r#"[[1,["y"]],[2,["x","z","v"]],[5,["w"]]]"#);
+    let read: AvlMultiMap<i32, char> = serde_json::from_str(&json).unwrap();
+    assert_eq!((read.len(), read.distinct_len(), read.count(2), read.rank(5)), (5, 3, 3, 4));
+    assert_eq!(read.get(2), Some(vec!['x', 'z', 'v']));
+}
+
+#[test]
+fn reject_unsorted_input() {
+    let err = serde_json::from_str::<AvlTree<i32>>("[[1,1],[3,1],[2,1]]").err().unwrap();
+    assert!(err.to_string().contains("not strictly ascending at 2"), "{}", err);
+    assert!(serde_json::from_str::<AvlTree<i32>>("[[1,1],[1,2]]").is_err());
+    assert!(serde_json::from_str::<AvlMap<i32, char>>(r#"[[2,"a"],[1,"b"]]"#).is_err());
+    assert!(serde_json::from_str::<AvlMap<i32, char>>(r#"[[1,"a"],[1,"b"]]"#).is_err());
+    assert!(serde_json::from_str::<AvlMultiMap<i32, char>>(r#"[[2,["a"]],[1,["b"]]]"#).is_err());
+}
+
+#[test]
+fn reject_zero_count() {
+    let err = serde_json::from_str::<AvlTree<i32>>("[[1,1],[3,0]]").err().unwrap();
+    assert!(err.to_string().contains("zero at 1"), "{}", err);
+    let err = serde_json::from_str::<AvlMultiMap<i32, char>>(r#"[[1,["a"]],[3,[]]]"#).err().unwrap();
+    assert!(err.to_string().contains("zero at 1"), "{}", err);
+}
@@ -0,0 +1,89 @@
+//! Tests of the sorting functions against the stable sort of the standard library.
+
+use avlsort::traits::OrdEqElem;
+use avlsort::{argsort, sort, sort_by_key, sort_dedup_counts};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+/// Return random values in `0..range`.
+fn random(seed: u64, len: usize, range: i32) -> Vec<i32> {
+    let mut rng = StdRng::seed_from_u64(seed);
+    (0..len).map(|_| rng.gen_range(0..range)).collect()
+}
+
+/// Assert that `indices` is a permutation of `0..len`.
+fn assert_permutation(indices: &[usize], len: usize) {
+    let mut seen = vec![false; len];
+    for &i in indices {
+        assert!(i < len && !seen[i], "{:?} is not a permutation", indices);
+        seen[i] = true;
+    }
+    assert_eq!(indices.len(), len);
+}
+
+#[test]
+fn argsort_is_stable_permutation() {
+    for &(len, range) in [(0, 1), (1, 1), (100, 5), (100, 1000), (1000, 30)].iter() {
+        let v = random(len as u64, len, range);
+        let indices = argsort(&v);
+        assert_permutation(&indices, len);
+        let mut expected: Vec<usize> = (0..len).collect();
+        expected.sort_by_key(|&i| v[i]);
+        assert_eq!(indices, expected);
+    }
+}
+
+#[test]
+fn argsort_ord_eq_elem_is_stable_permutation() {
+    let v: Vec<f64> = random(0, 500, 20).into_iter().map(f64::from).collect();
+    let elems: Vec<OrdEqElem<f64>> = v.iter().map(|&x| OrdEqElem::new(x)).collect();
+    let indices = argsort(&elems);
+    assert_permutation(&indices, v.len());
+    let mut expected: Vec<usize> = (0..v.len()).collect();
+    expected.sort_by(|&i, &j| v[i].partial_cmp(&v[j]).unwrap());
+    assert_eq!(indices, expected);
+}
+
+#[test]
+fn sort_matches_std() {
+    let v: Vec<f64> = random(1, 1000, 50).into_iter().map(|x| f64::from(x) - 25.5).collect();
+    let mut sorted = v.clone();
+    sort(&mut sorted);
+    let mut expected = v;
+    expected.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    assert_eq!(sorted, expected);
+}
+
+#[test]
+fn sort_by_key_is_stable() {
+    let mut v: Vec<(i32, usize)> = random(2, 300, 10).into_iter().enumerate().map(|(i, k)| (k, i)).collect();
+    let mut expected = v.clone();
+    expected.sort_by_key(|&(k, _)| k);
+    sort_by_key(&mut v, |&(k, _)| k);
+    assert_eq!(v, expected);
+}
+
+#[test]
+fn sort_dedup_counts_merges_ord_eq_elem() {
+    let elems: Vec<OrdEqElem<i32>> = [3, 1, 3, 2, 3, 1].iter().map(|&x| OrdEqElem::new(x)).collect();
+    let counts: Vec<(i32, usize)> = sort_dedup_counts(&elems).into_iter().map(|(x, c)| (x.value, c)).collect();
+    assert_eq!(counts, [(1, 2), (2, 1), (3, 3)]);
+}
+
+#[test]
+#[should_panic(expected = "NaN cannot be sorted")]
+fn sort_rejects_nan() {
+    sort(&mut [3.0, f64::NAN, 1.0, 2.0, f64::NAN]);
+}
+
+#[test]
+#[should_panic(expected = "NaN cannot be sorted")]
+fn argsort_rejects_nan() {
+    argsort(&[3.0, f64::NAN, 1.0, 2.0, f64::NAN]);
+}
+
+#[test]
+#[should_panic(expected = "NaN cannot be sorted")]
+fn sort_by_key_rejects_nan() {
+    sort_by_key(&mut [1, 2, 3], |&x| if x == 2 { f64::NAN } else { f64::from(x) });
+}
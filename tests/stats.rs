@@ -0,0 +1,213 @@
+//! Tests of the statistics against reference values.
+//!
+//! The reference values follow `scipy.stats.mannwhitneyu(x, y, method="asymptotic")`,
+//! whose standard score is corrected for ties and for continuity,
+//! `scipy.stats.wilcoxon(x, y, method="approx")`, whose standard score is corrected for ties,
+//! the statistic of `scipy.stats.ks_2samp(a, b)` and `scipy.stats.kendalltau(x, y)`.
+//! The counts of pairs and the empirical distribution functions are also checked against brute force.
+
+use avlsort::stats::{
+    count_inversions, kendall_distance, kendall_tau_a, kendall_tau_b, ks_one_sample, ks_two_sample, mann_whitney_u,
+    mann_whitney_u_trees, wilcoxon, RankTest,
+};
+use avlsort::tree::AvlTree;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+/// Assert that `actual` has the statistic, the standard score and the p-value of `expected`.
+///
+/// The p-value is computed by an approximation of erfc with the fractional error less than 1.2e-7.
+fn assert_rank_test(actual: Option<RankTest>, expected: (f64, f64, f64)) {
+    let actual = actual.expect("the test is defined");
+    assert_eq!(actual.statistic, expected.0);
+    assert!((actual.z - expected.1).abs() < 1e-12, "z: {} != {}", actual.z, expected.1);
+    assert!((actual.p_value - expected.2).abs() < 1e-6, "p: {} != {}", actual.p_value, expected.2);
+}
+
+#[test]
+fn mann_whitney_u_without_ties() {
+    // The example in the documentation of scipy, with the p-value 0.11134688653314041.
+    let males = [19, 22, 16, 29, 24];
+    let females = [20, 11, 17, 12];
+    let expected = (17.0, 1.5921683328090657, 0.11134688653314041);
+    assert_rank_test(mann_whitney_u(&males, &females), expected);
+    assert_rank_test(mann_whitney_u_trees(&males.iter().copied().collect(), &females.iter().copied().collect()), expected);
+}
+
+#[test]
+fn mann_whitney_u_with_ties() {
+    let x = [1, 2, 2, 3, 3, 3, 4];
+    let y = [3, 4, 4, 5, 5, 6];
+    assert_rank_test(mann_whitney_u(&x, &y), (3.5, -2.483773282901211, 0.012999854364387314));
+
+    // Ties within `x`, within `y` and between them.
+    let x = [1.5, 2.5, 2.5, 7.0];
+    let y = [2.5, 2.5, 3.0, 8.0, 9.0, 1.0];
+    assert_rank_test(mann_whitney_u_trees(&x.iter().copied().collect(), &y.iter().copied().collect()), (9.0, -0.5499266813300748, 0.5823696629652255));
+}
+
+#[test]
+fn mann_whitney_u_undefined() {
+    assert_eq!(mann_whitney_u::<i32>(&[], &[1, 2]), None);
+    assert_eq!(mann_whitney_u(&[1, 2], &[]), None);
+    assert_eq!(mann_whitney_u(&[3, 3], &[3, 3, 3]), None);
+}
+
+#[test]
+fn ks_two_sample_statistic() {
+    let cases: [(&[f64], &[f64], f64); 4] = [
+        (&[1.0, 2.0, 3.0], &[4.0, 5.0, 6.0], 1.0),
+        (&[1.0, 2.0, 3.0, 4.0], &[3.0, 4.0, 5.0, 6.0], 0.5),
+        (&[1.0, 2.0, 2.0, 3.0, 3.0, 3.0, 4.0], &[3.0, 4.0, 4.0, 5.0, 5.0, 6.0], 0.6904761904761905),
+        (&[0.5, 1.0, 1.0, 2.0, 3.5], &[1.0, 1.0, 2.5], 0.2),
+    ];
+    for &(a, b, expected) in cases.iter() {
+        let (a, b): (AvlTree<_>, AvlTree<_>) = (a.iter().copied().collect(), b.iter().copied().collect());
+        let d = ks_two_sample(&a, &b).unwrap();
+        assert!((d - expected).abs() < 1e-12, "{} != {}", d, expected);
+        assert_eq!(ks_two_sample(&b, &a), Some(d));
+    }
+    assert_eq!(ks_two_sample(&[1, 1, 2].iter().copied().collect(), &[2, 1, 1].iter().copied().collect()), Some(0.0));
+    assert_eq!(ks_two_sample(&[1].iter().copied().collect(), &AvlTree::new()), None);
+}
+
+/// Return `(concordant, discordant, tied_in_x, tied_in_y)` over all pairs of `x` and `y`.
+fn brute_pairs(x: &[i32], y: &[i32]) -> (i64, i64, i64, i64) {
+    let (mut c, mut d, mut t_x, mut t_y) = (0, 0, 0, 0);
+    for i in 0..x.len() {
+        for j in i + 1..x.len() {
+            let s = (x[i] - x[j]).signum() * (y[i] - y[j]).signum();
+            c += (s > 0) as i64;
+            d += (s < 0) as i64;
+            t_x += (x[i] == x[j]) as i64;
+            t_y += (y[i] == y[j]) as i64;
+        }
+    }
+    (c, d, t_x, t_y)
+}
+
+#[test]
+fn kendall_match_brute_force() {
+    let mut rng = StdRng::seed_from_u64(28);
+    for _ in 0..300 {
+        let n = rng.gen_range(0..30);
+        let range = rng.gen_range(1..10);
+        let x: Vec<i32> = (0..n).map(|_| rng.gen_range(0..range)).collect();
+        let y: Vec<i32> = (0..n).map(|_| rng.gen_range(0..range)).collect();
+
+        let n_inv = (0..n).flat_map(|i| (i + 1..n).map(move |j| (i, j))).filter(|&(i, j)| x[i] > x[j]).count();
+        assert_eq!(count_inversions(&x), n_inv as u64, "{:?}", x);
+
+        let (c, d, t_x, t_y) = brute_pairs(&x, &y);
+        assert_eq!(kendall_distance(&x, &y), d as u64, "{:?} {:?}", x, y);
+        let n_all = (n * n.saturating_sub(1) / 2) as i64;
+        let tau_a = kendall_tau_a(&x, &y);
+        if n_all == 0 {
+            assert_eq!(tau_a, None);
+        } else {
+            let expected = (c - d) as f64 / n_all as f64;
+            assert!((tau_a.unwrap() - expected).abs() < 1e-12, "{:?} != {}", tau_a, expected);
+        }
+        let tau_b = kendall_tau_b(&x, &y);
+        if n_all == t_x || n_all == t_y {
+            assert_eq!(tau_b, None);
+        } else {
+            let expected = (c - d) as f64 / (((n_all - t_x) * (n_all - t_y)) as f64).sqrt();
+            assert!((tau_b.unwrap() - expected).abs() < 1e-12, "{:?} != {}", tau_b, expected);
+        }
+    }
+}
+
+#[test]
+fn kendall_tau_b_with_ties() {
+    // The example in the documentation of `scipy.stats.kendalltau`.
+    let x = [12, 2, 1, 12, 2];
+    let y = [1, 4, 7, 1, 0];
+    let tau_b = kendall_tau_b(&x, &y).unwrap();
+    assert!((tau_b - -0.47140452079103173).abs() < 1e-12, "{}", tau_b);
+    assert_eq!(kendall_tau_a(&x, &y), Some(-0.4));
+    assert_eq!(kendall_distance(&x, &y), 6);
+}
+
+#[test]
+#[should_panic(expected = "NaN cannot be counted")]
+fn count_inversions_rejects_nan() {
+    count_inversions(&[3.0, f64::NAN, 1.0, 2.0]);
+}
+
+#[test]
+fn wilcoxon_without_ties() {
+    // The example in the documentation of `scipy.stats.wilcoxon` with `method="approx"`.
+    let d = [6, 8, 14, 16, 23, 24, 28, 29, 41, -48, 49, 56, 60, -67, 75];
+    assert_rank_test(wilcoxon(&d, &[0; 15]), (24.0, 2.0446626032894333, 0.040888132911855925));
+    let neg: Vec<i32> = d.iter().map(|&x| -x).collect();
+    assert_rank_test(wilcoxon(&neg, &[0; 15]), (24.0, -2.0446626032894333, 0.040888132911855925));
+}
+
+#[test]
+fn wilcoxon_with_ties_and_zeros() {
+    let x = [1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0];
+    let y = [2.0, 1.0, 3.0, 2.0, 7.0, 3.0, 5.0];
+    assert_rank_test(wilcoxon(&x, &y), (5.5, 1.0629880069054678, 0.28778739015415017));
+
+    let x = [3, 5, 5, 2, 8, 1, 9, 4];
+    let y = [1, 5, 7, 4, 4, 3, 2, 4];
+    assert_rank_test(wilcoxon(&x, &y), (7.5, 0.6469966392206304, 0.5176341185833456));
+}
+
+#[test]
+fn wilcoxon_undefined() {
+    assert_eq!(wilcoxon::<i32>(&[], &[]), None);
+    assert_eq!(wilcoxon(&[1, 2, 3], &[1, 2, 3]), None);
+}
+
+#[test]
+#[should_panic(expected = "NaN cannot be ranked")]
+fn wilcoxon_rejects_nan() {
+    wilcoxon(&[1.0, f64::NAN, 3.0], &[0.0, 0.0, 0.0]);
+}
+
+#[test]
+#[should_panic(expected = "NaN cannot be ranked")]
+fn mann_whitney_u_rejects_nan() {
+    mann_whitney_u(&[1.0, 2.0, 3.0], &[f64::NAN, 1.5]);
+}
+
+#[test]
+fn ecdf_matches_brute_force() {
+    let mut rng = StdRng::seed_from_u64(31);
+    for _ in 0..100 {
+        let values: Vec<i32> = (0..rng.gen_range(1..40)).map(|_| rng.gen_range(0..20)).collect();
+        let tree: AvlTree<_> = values.iter().copied().collect();
+        for x in -1..21 {
+            let expected = values.iter().filter(|&&v| v <= x).count() as f64 / values.len() as f64;
+            assert_eq!(tree.ecdf(x), Some(expected), "{:?} at {}", values, x);
+        }
+    }
+    assert_eq!(AvlTree::<i32>::new().ecdf(0), None);
+}
+
+#[test]
+fn ks_one_sample_statistic() {
+    let uniform = |x: f64| x.clamp(0.0, 1.0);
+    let tree: AvlTree<_> = [0.7, 0.1, 0.4].iter().copied().collect();
+    let d = ks_one_sample(&tree, uniform).unwrap();
+    assert!((d - 0.3).abs() < 1e-12, "{}", d);
+    assert_eq!(ks_one_sample(&AvlTree::new(), uniform), None);
+
+    // Compare with the distance at both sides of each step of the empirical distribution function.
+    let mut rng = StdRng::seed_from_u64(31);
+    for _ in 0..100 {
+        let values: Vec<i32> = (0..rng.gen_range(1..40)).map(|_| rng.gen_range(0..20)).collect();
+        let tree: AvlTree<_> = values.iter().copied().collect();
+        let cdf = |x: i32| (x as f64 + 0.5) / 20.0;
+        let n = values.len() as f64;
+        let expected = values.iter().map(|&x| {
+            let below = values.iter().filter(|&&v| v < x).count() as f64 / n;
+            let upto = values.iter().filter(|&&v| v <= x).count() as f64 / n;
+            (cdf(x) - below).abs().max((upto - cdf(x)).abs())
+        }).fold(0.0, f64::max);
+        let d = ks_one_sample(&tree, cdf).unwrap();
+        assert!((d - expected).abs() < 1e-12, "{:?}: {} != {}", values, d, expected);
+    }
+}
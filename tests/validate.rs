@@ -0,0 +1,118 @@
+//! Tests of `AvlTree::validate` on trees corrupted by hand.
+
+use avlsort::error::{InvariantReport, Violation};
+use avlsort::node::{AvlNode, Direction};
+use avlsort::tree::AvlTree;
+
+use Direction::{Left as L, Right as R};
+
+/// Return the perfect tree of 1 to 7, whose root is 4.
+fn perfect_tree() -> AvlTree<i32> {
+    let mut tree = AvlTree::new();
+    for value in 1..=7 {
+        tree.push(value);
+    }
+    tree.validate().unwrap();
+    tree
+}
+
+/// Apply `f` to the node at `path` of `tree`.
+fn corrupt<F: FnOnce(&mut AvlNode<i32>)>(tree: &mut AvlTree<i32>, path: &[Direction], f: F) {
+    let root = tree.root.as_mut().unwrap();
+    let mut node = match path.first() {
+        None => return f(root),
+        Some(L) => root.left.clone().unwrap(),
+        Some(R) => root.right.clone().unwrap(),
+    };
+    for dir in &path[1..] {
+        let child = match dir {
+            L => node.lock().unwrap().left.clone().unwrap(),
+            R => node.lock().unwrap().right.clone().unwrap(),
+        };
+        node = child;
+    }
+    f(&mut node.lock().unwrap());
+}
+
+fn report(path: &[Direction], violation: Violation) -> Result<(), InvariantReport> {
+    Err(InvariantReport { path: path.to_vec(), violation })
+}
+
+#[test]
+fn order() {
+    let mut tree = perfect_tree();
+    corrupt(&mut tree, &[L], |node| node.value = 5);
+    assert_eq!(tree.validate(), report(&[L], Violation::Order));
+
+    // The bound comes from the root, not only from the parent.
+    let mut tree = perfect_tree();
+    corrupt(&mut tree, &[R, L], |node| node.value = 3);
+    assert_eq!(tree.validate(), report(&[R, L], Violation::Order));
+}
+
+#[test]
+fn unbalanced() {
+    let mut tree = perfect_tree();
+    corrupt(&mut tree, &[], |node| node.diff = 2);
+    assert_eq!(tree.validate(), report(&[], Violation::Unbalanced { diff: 2 }));
+}
+
+#[test]
+fn diff() {
+    let mut tree = perfect_tree();
+    corrupt(&mut tree, &[R], |node| node.diff = -1);
+    assert_eq!(tree.validate(), report(&[R], Violation::Diff { diff: -1, actual: 0 }));
+
+    // Dropping a leaf leaves the parent's diff stale.
+    let mut tree = perfect_tree();
+    corrupt(&mut tree, &[L], |node| node.left = None);
+    assert_eq!(tree.validate(), report(&[L], Violation::Diff { diff: 0, actual: -1 }));
+}
+
+#[test]
+fn less_count() {
+    let mut tree = perfect_tree();
+    corrupt(&mut tree, &[], |node| node.n_ledu.0 = 2);
+    assert_eq!(tree.validate(), report(&[], Violation::LessCount { n_less: 2, actual: 3 }));
+}
+
+#[test]
+fn max_dup() {
+    let mut tree = perfect_tree();
+    corrupt(&mut tree, &[L, L], |node| node.max_dup = 5);
+    assert_eq!(tree.validate(), report(&[L, L], Violation::MaxDup { max_dup: 5, actual: 0 }));
+
+    // A duplicate added without updating the ancestors is found at the parent.
+    let mut tree = perfect_tree();
+    corrupt(&mut tree, &[R, R], |node| {
+        node.n_ledu.1 = 1;
+        node.update();
+    });
+    assert_eq!(tree.validate(), report(&[R], Violation::MaxDup { max_dup: 0, actual: 1 }));
+}
+
+#[test]
+fn cached() {
+    let mut tree = perfect_tree();
+    corrupt(&mut tree, &[], |node| node.len = 8);
+    assert_eq!(tree.validate(), report(&[], Violation::Cached { cached: (8, 7, 3), actual: (7, 7, 3) }));
+
+    let mut tree = perfect_tree();
+    corrupt(&mut tree, &[L], |node| node.height = 1);
+    assert_eq!(tree.validate(), report(&[L], Violation::Cached { cached: (3, 3, 1), actual: (3, 3, 2) }));
+}
+
+#[test]
+fn aliased() {
+    let mut tree = perfect_tree();
+    corrupt(&mut tree, &[], |node| node.right = node.left.clone());
+    assert_eq!(tree.validate(), report(&[R], Violation::Aliased));
+}
+
+#[test]
+fn report_display() {
+    let mut tree = perfect_tree();
+    corrupt(&mut tree, &[R, L], |node| node.value = 3);
+    let report = tree.validate().unwrap_err();
+    assert_eq!(report.to_string(), "invariant broken at root/R/L: the value is out of order");
+}
@@ -0,0 +1,74 @@
+//! Tests of `SlidingWindow` against sorting the values in the window.
+
+use avlsort::window::SlidingWindow;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+const QS: [f64; 7] = [0.0, 0.1, 0.25, 0.5, 0.75, 0.9, 1.0];
+
+/// Return the `q`-quantile of `values` interpolated like `SlidingWindow::quantile`.
+fn quantile(values: &[i32], q: f64) -> Option<f64> {
+    let mut sorted = values.to_vec();
+    sorted.sort_unstable();
+    let h = (sorted.len().checked_sub(1)?) as f64 * q;
+    let (lo, hi) = (h.floor() as usize, h.ceil() as usize);
+    Some(f64::from(sorted[lo]) + (h - h.floor()) * f64::from(sorted[hi] - sorted[lo]))
+}
+
+#[test]
+fn count_window_matches_brute_force() {
+    for &(n, range) in [(1, 10), (2, 3), (5, 4), (16, 100), (50, 10)].iter() {
+        let mut rng = StdRng::seed_from_u64(n as u64);
+        let mut window = SlidingWindow::new(n);
+        let mut pushed = Vec::new();
+        for _ in 0..500 {
+            let value = rng.gen_range(0..range);
+            window.push(value);
+            pushed.push(value);
+            let last = &pushed[pushed.len().saturating_sub(n)..];
+            assert_eq!(window.len(), last.len());
+            assert_eq!(window.median(), quantile(last, 0.5), "n={} {:?}", n, last);
+            for &q in QS.iter() {
+                assert_eq!(window.quantile(q), quantile(last, q), "n={} q={} {:?}", n, q, last);
+            }
+            assert_eq!(window.min(), last.iter().copied().min());
+            assert_eq!(window.max(), last.iter().copied().max());
+            assert_eq!(window.rank(range / 2), last.iter().filter(|&&x| x < range / 2).count());
+            assert_eq!(window.count(range / 2), last.iter().filter(|&&x| x == range / 2).count());
+            window.tree().validate().unwrap();
+        }
+    }
+}
+
+#[test]
+fn horizon_window_matches_brute_force() {
+    let horizon = 20;
+    let mut rng = StdRng::seed_from_u64(0);
+    let mut window = SlidingWindow::with_horizon(horizon);
+    let mut pushed: Vec<(u64, i32)> = Vec::new();
+    let mut now = 0;
+    for _ in 0..500 {
+        now += rng.gen_range(0..5);
+        let value = rng.gen_range(0..30);
+        window.push_at(now, value);
+        pushed.push((now, value));
+        let last: Vec<i32> = pushed.iter().filter(|&&(t, _)| now - t < horizon).map(|&(_, x)| x).collect();
+        assert_eq!(window.len(), last.len());
+        for &q in QS.iter() {
+            assert_eq!(window.quantile(q), quantile(&last, q), "q={} {:?}", q, last);
+        }
+        window.tree().validate().unwrap();
+    }
+    window.advance(now + horizon);
+    assert!(window.is_empty());
+    assert_eq!(window.median(), None);
+    assert!(window.tree().is_empty());
+}
+
+#[test]
+#[should_panic(expected = "NaN cannot be pushed to the window")]
+fn window_rejects_nan() {
+    let mut window = SlidingWindow::new(3);
+    window.push(1.0);
+    window.push(f64::NAN);
+}